@@ -0,0 +1,286 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{command, Manager, State};
+
+use super::database::collect_asset_files;
+use crate::db::AppState;
+use crate::error::CommandError;
+
+// ============================================
+// App State Commands
+// ============================================
+
+/// Key of the `app_state` row holding `LastOpened`
+const LAST_OPENED_KEY: &str = "last_opened";
+
+/// Where the user was when they last switched project or board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LastOpened {
+    pub project_id: String,
+    pub board_id: Option<String>,
+    pub opened_at: i64,
+}
+
+/// Remember the open project (and board, if any) so the next launch can return to it
+/// The board must belong to the project
+#[command]
+pub async fn set_last_opened(
+    state: State<'_, AppState>,
+    project_id: String,
+    board_id: Option<String>,
+) -> Result<LastOpened, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let project_exists = sqlx::query("SELECT 1 FROM projects WHERE id = ?")
+        .bind(&project_id)
+        .fetch_optional(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to look up project", e))?
+        .is_some();
+    if !project_exists {
+        return Err(CommandError::NotFound(format!("project not found: {}", project_id)));
+    }
+
+    if let Some(board_id) = &board_id {
+        let board_project: Option<String> = sqlx::query_scalar("SELECT project_id FROM boards WHERE id = ?")
+            .bind(board_id)
+            .fetch_optional(&state.pool())
+            .await
+            .map_err(|e| CommandError::db("Failed to look up board", e))?;
+        match board_project {
+            None => return Err(CommandError::NotFound(format!("board not found: {}", board_id))),
+            Some(board_project) if board_project != project_id => {
+                return Err(CommandError::Validation(format!(
+                    "board {} is not in project {}",
+                    board_id, project_id
+                )));
+            }
+            Some(_) => {}
+        }
+    }
+
+    let last_opened = LastOpened { project_id, board_id, opened_at: now };
+    let value = serde_json::to_string(&last_opened)
+        .map_err(|e| CommandError::internal("Failed to encode last opened", e))?;
+    sqlx::query(
+        "INSERT INTO app_state (key, value, updated_at) VALUES (?, ?, ?)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+    )
+    .bind(LAST_OPENED_KEY)
+    .bind(&value)
+    .bind(now)
+    .execute(&state.writer())
+    .await
+    .map_err(|e| CommandError::db("Failed to save last opened", e))?;
+
+    Ok(last_opened)
+}
+
+/// Get the project and board the user last had open
+/// None on first run or once that project has been deleted; a deleted board
+/// leaves only the project
+#[command]
+pub async fn get_last_opened(state: State<'_, AppState>) -> Result<Option<LastOpened>, CommandError> {
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM app_state WHERE key = ?")
+        .bind(LAST_OPENED_KEY)
+        .fetch_optional(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to get last opened", e))?;
+    let Some(value) = value else {
+        return Ok(None);
+    };
+
+    // A value that doesn't parse is treated like a first run rather than an error
+    let mut last_opened: LastOpened = match serde_json::from_str(&value) {
+        Ok(last_opened) => last_opened,
+        Err(e) => {
+            log::warn!("Ignoring unreadable last opened state: {}", e);
+            return Ok(None);
+        }
+    };
+
+    let (project_exists, board_exists): (bool, bool) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM projects WHERE id = ?1),
+                EXISTS (SELECT 1 FROM boards WHERE id = ?2 AND project_id = ?1)",
+    )
+    .bind(&last_opened.project_id)
+    .bind(&last_opened.board_id)
+    .fetch_one(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to look up last opened", e))?;
+    if !project_exists {
+        return Ok(None);
+    }
+    if !board_exists {
+        last_opened.board_id = None;
+    }
+    Ok(Some(last_opened))
+}
+
+// ============================================
+// Assets Root
+// ============================================
+
+/// Key of the `app_state` row holding the assets folder chosen by the user
+const ASSETS_ROOT_KEY: &str = "assets_root";
+
+/// The folder asset paths are relative to: the one set with `set_assets_root`,
+/// or `<app data>/assets` by default
+pub(crate) async fn assets_root(app: &tauri::AppHandle, state: &AppState) -> Result<PathBuf, CommandError> {
+    match configured_assets_root(&state.pool()).await? {
+        Some(root) => Ok(root),
+        None => default_assets_root(app),
+    }
+}
+
+fn default_assets_root(app: &tauri::AppHandle) -> Result<PathBuf, CommandError> {
+    let app_data_dir = app.path()
+        .app_data_dir()
+        .map_err(|e| CommandError::internal("Failed to get app data dir", e))?;
+    Ok(app_data_dir.join("assets"))
+}
+
+/// Store the assets folder setting; None goes back to the default folder
+/// Restores keep the current setting this way, since the database they bring back
+/// may name a folder that no longer holds the files
+pub(crate) async fn save_assets_root(pool: &sqlx::SqlitePool, root: Option<&Path>) -> Result<(), CommandError> {
+    let result = match root {
+        Some(root) => {
+            sqlx::query(
+                "INSERT INTO app_state (key, value, updated_at) VALUES (?, ?, ?)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            )
+            .bind(ASSETS_ROOT_KEY)
+            .bind(root.to_string_lossy().to_string())
+            .bind(chrono::Utc::now().timestamp_millis())
+            .execute(pool)
+            .await
+        }
+        None => {
+            sqlx::query("DELETE FROM app_state WHERE key = ?")
+                .bind(ASSETS_ROOT_KEY)
+                .execute(pool)
+                .await
+        }
+    };
+    result.map_err(|e| CommandError::db("Failed to save assets folder", e))?;
+    Ok(())
+}
+
+/// The assets folder setting as stored, None when the default folder is used
+pub(crate) async fn configured_assets_root(pool: &sqlx::SqlitePool) -> Result<Option<PathBuf>, CommandError> {
+    let configured: Option<String> = sqlx::query_scalar("SELECT value FROM app_state WHERE key = ?")
+        .bind(ASSETS_ROOT_KEY)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| CommandError::db("Failed to get assets folder", e))?;
+    Ok(configured.map(PathBuf::from))
+}
+
+/// Create `dir` if needed and check a file can be written in it
+fn ensure_writable(dir: &Path) -> Result<(), CommandError> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| CommandError::Validation(format!("cannot create {}: {}", dir.display(), e)))?;
+    let probe = dir.join(format!(".notly_write_test_{}", uuid::Uuid::new_v4()));
+    std::fs::write(&probe, b"")
+        .map_err(|e| CommandError::Validation(format!("{} is not writable: {}", dir.display(), e)))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Move every file below `from` to the same relative path below `to`
+/// Nothing is moved if any of them already exists in `to`. Files are copied
+/// first and the originals removed once all copies succeeded, so a failure
+/// leaves the old folder complete. Returns the number of files moved
+fn move_assets(from: &Path, to: &Path) -> Result<u64, CommandError> {
+    let mut files = Vec::new();
+    if from.exists() {
+        collect_asset_files(from, "", &mut files)
+            .map_err(|e| CommandError::io("Failed to list assets", e))?;
+    }
+    if let Some(existing) = files.iter().find(|file| to.join(file).exists()) {
+        return Err(CommandError::Conflict(format!("{} already exists in {}", existing, to.display())));
+    }
+
+    let mut copied = Vec::with_capacity(files.len());
+    for file in &files {
+        let target = to.join(file);
+        let result = target
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::copy(from.join(file), &target));
+        if let Err(e) = result {
+            for target in &copied {
+                let _ = std::fs::remove_file(target);
+            }
+            return Err(CommandError::io(&format!("Failed to move {}", file), e));
+        }
+        copied.push(target);
+    }
+
+    for file in &files {
+        if let Err(e) = std::fs::remove_file(from.join(file)) {
+            log::warn!("Failed to remove moved asset {}: {}", file, e);
+        }
+    }
+    Ok(files.len() as u64)
+}
+
+/// The assets folder after `set_assets_root`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetsRootChange {
+    pub path: String,
+    /// Files moved over from the previous folder (0 unless `migrate` was set)
+    pub migrated_files: u64,
+}
+
+/// Get the folder assets are stored in
+#[command]
+pub async fn get_assets_root(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<String, CommandError> {
+    Ok(assets_root(&app, &state).await?.to_string_lossy().to_string())
+}
+
+/// Store assets in `path` from now on; an empty path goes back to the default folder
+/// The folder must be absolute and writable, and is created if missing. With
+/// `migrate`, the files in the current folder are moved over; otherwise they stay
+/// where they are and only files already in `path` can be found
+#[command]
+pub async fn set_assets_root(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    migrate: Option<bool>,
+) -> Result<AssetsRootChange, CommandError> {
+    let path = path.trim();
+    let new_root = if path.is_empty() { default_assets_root(&app)? } else { PathBuf::from(path) };
+    if !new_root.is_absolute() {
+        return Err(CommandError::Validation(format!("assets folder must be an absolute path: {}", path)));
+    }
+    ensure_writable(&new_root)?;
+
+    let old_root = assets_root(&app, &state).await?;
+    let same_folder = matches!(
+        (old_root.canonicalize(), new_root.canonicalize()),
+        (Ok(a), Ok(b)) if a == b
+    );
+    let mut migrated_files = 0;
+    if migrate.unwrap_or(false) && !same_folder {
+        if new_root.starts_with(&old_root) || old_root.starts_with(&new_root) {
+            return Err(CommandError::Validation(
+                "cannot move assets into a folder inside the current one, or the other way round".to_string(),
+            ));
+        }
+        let (from, to) = (old_root.clone(), new_root.clone());
+        migrated_files = tauri::async_runtime::spawn_blocking(move || move_assets(&from, &to))
+            .await
+            .map_err(|e| CommandError::internal("Failed to move assets", e))??;
+    }
+
+    save_assets_root(&state.writer(), (!path.is_empty()).then_some(new_root.as_path())).await?;
+
+    log::info!("Assets folder set to {:?} ({} files moved from {:?})", new_root, migrated_files, old_root);
+    Ok(AssetsRootChange { path: new_root.to_string_lossy().to_string(), migrated_files })
+}
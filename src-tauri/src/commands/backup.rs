@@ -0,0 +1,650 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{command, Manager, State};
+
+use super::app_state::{assets_root, configured_assets_root, save_assets_root};
+use super::boards::write_pending_snapshots;
+use super::bundle::{build_project_bundle, copy_bundle_assets, import_bundle, ProjectBundle};
+use super::database::collect_asset_files;
+use crate::db::{migrations, AppState};
+use crate::error::CommandError;
+
+// ============================================
+// Backup Commands
+// ============================================
+
+/// File name prefix shared by database backups and their asset archives
+const BACKUP_PREFIX: &str = "notly_";
+
+fn backups_dir(app: &tauri::AppHandle) -> Result<PathBuf, CommandError> {
+    let app_data_dir = app.path()
+        .app_data_dir()
+        .map_err(|e| CommandError::internal("Failed to get app data dir", e))?;
+
+    Ok(app_data_dir.join("backups"))
+}
+
+/// Asset archive written next to a database backup
+fn assets_archive_path(backup_path: &Path) -> PathBuf {
+    let stem = backup_path.file_stem().and_then(|s| s.to_str()).unwrap_or("notly");
+    backup_path.with_file_name(format!("{}_assets.zip", stem))
+}
+
+/// Zip every file in `assets_dir` into `target`, streaming each file into the archive
+fn zip_assets(assets_dir: &Path, target: &Path) -> Result<(), CommandError> {
+    let mut files = Vec::new();
+    if assets_dir.exists() {
+        collect_asset_files(assets_dir, "", &mut files)
+            .map_err(|e| CommandError::io("Failed to list assets", e))?;
+    }
+
+    let archive = std::fs::File::create(target)
+        .map_err(|e| CommandError::io("Failed to create assets archive", e))?;
+    let mut zip = zip::ZipWriter::new(archive);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for relative_path in &files {
+        let mut file = std::fs::File::open(assets_dir.join(relative_path))
+            .map_err(|e| CommandError::io(&format!("Failed to read asset {}", relative_path), e))?;
+        let size = file
+            .metadata()
+            .map_err(|e| CommandError::io(&format!("Failed to read asset {}", relative_path), e))?
+            .len();
+        zip.start_file(relative_path.as_str(), options.large_file(size >= u32::MAX as u64))
+            .map_err(|e| CommandError::internal("Failed to write assets archive", e))?;
+        std::io::copy(&mut file, &mut zip)
+            .map_err(|e| CommandError::io("Failed to write assets archive", e))?;
+    }
+
+    zip.finish()
+        .map_err(|e| CommandError::internal("Failed to write assets archive", e))?;
+    Ok(())
+}
+
+/// Delete all but the `keep` most recent backups (and their asset archives)
+fn prune_backups(dir: &Path, keep: usize) -> Result<(), CommandError> {
+    // Names embed a millisecond timestamp, so sorting by name sorts by age
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| CommandError::io("Failed to list backups", e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "db")
+                && path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(BACKUP_PREFIX))
+        })
+        .collect();
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(keep);
+    for backup in &backups[..excess] {
+        std::fs::remove_file(backup)
+            .map_err(|e| CommandError::io("Failed to delete old backup", e))?;
+        let archive = assets_archive_path(backup);
+        if archive.exists() {
+            std::fs::remove_file(&archive)
+                .map_err(|e| CommandError::io("Failed to delete old backup", e))?;
+        }
+        log::info!("Deleted old backup: {:?}", backup);
+    }
+    Ok(())
+}
+
+/// Back up the database to `backups/notly_<timestamp>.db` and return its path
+/// Uses SQLite's online backup API, which takes a consistent copy while the database
+/// is in use. With `include_assets` the assets folder is zipped alongside it; with
+/// `max_backups` only that many of the most recent backups are kept, so it must be at
+/// least 1
+#[command]
+pub async fn create_backup(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    include_assets: bool,
+    max_backups: Option<usize>,
+) -> Result<String, CommandError> {
+    if max_backups == Some(0) {
+        return Err(CommandError::Validation("max_backups must be at least 1".to_string()));
+    }
+
+    let dir = backups_dir(&app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| CommandError::io("Failed to create backups directory", e))?;
+
+    // A snapshot still in the autosave buffer is part of the current state
+    write_pending_snapshots(&state, None).await?;
+
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let backup_path = dir.join(format!("{}{}.db", BACKUP_PREFIX, timestamp));
+
+    let assets_dir = if include_assets { Some(assets_root(&app, &state).await?) } else { None };
+    let db_path = state.path();
+    tauri::async_runtime::spawn_blocking({
+        let backup_path = backup_path.clone();
+        move || {
+            snapshot_database(&db_path, &backup_path)?;
+            match assets_dir {
+                Some(assets_dir) => zip_assets(&assets_dir, &assets_archive_path(&backup_path)),
+                None => Ok(()),
+            }
+        }
+    })
+    .await
+    .map_err(|e| CommandError::internal("Failed to back up database", e))??;
+
+    if let Some(max_backups) = max_backups {
+        prune_backups(&dir, max_backups)?;
+    }
+
+    log::info!("Created backup: {:?}", backup_path);
+    Ok(backup_path.to_string_lossy().to_string())
+}
+
+/// Header every SQLite database file starts with
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// Check that `path` is an intact SQLite database this app can open, without modifying it
+async fn validate_backup(path: &Path) -> Result<(), CommandError> {
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use std::io::Read;
+
+    let mut header = [0u8; 16];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map_err(|e| CommandError::io("Failed to read backup file", e))?;
+    if &header != SQLITE_HEADER {
+        return Err(CommandError::Validation(format!("Not a SQLite database: {}", path.display())));
+    }
+
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .map_err(|e| CommandError::db("Failed to open backup", e))?;
+    let integrity: Result<String, _> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_one(&pool)
+        .await;
+    let version: Result<i64, _> = sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(&pool)
+        .await;
+    pool.close().await;
+
+    match integrity {
+        Ok(status) if status == "ok" => {}
+        Ok(status) => return Err(CommandError::Validation(format!("Backup failed integrity check: {}", status))),
+        Err(e) => return Err(CommandError::db("Backup failed integrity check", e)),
+    }
+    let version = version.map_err(|e| CommandError::db("Failed to read backup schema version", e))?;
+    if version > migrations::latest_version() {
+        return Err(CommandError::Validation(format!(
+            "Backup schema version {} is newer than this app supports ({})",
+            version,
+            migrations::latest_version()
+        )));
+    }
+    Ok(())
+}
+
+/// Overwrite the database at `db_path` with `source` using SQLite's online backup API
+/// Safe while the app's own connections are open: they see the new contents afterwards
+async fn copy_database_into(source: PathBuf, db_path: PathBuf) -> Result<(), CommandError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut live = rusqlite::Connection::open(&db_path)?;
+        live.restore(rusqlite::DatabaseName::Main, &source, None::<fn(rusqlite::backup::Progress)>)
+    })
+    .await
+    .map_err(|e| CommandError::internal("Failed to restore database", e))?
+    .map_err(|e| CommandError::Db(format!("Failed to restore database: {}", e)))
+}
+
+/// Replace the live database's contents with a backup
+/// The backup is validated first (header magic, `PRAGMA integrity_check` and schema
+/// version); on failure nothing is touched. The current database is saved as
+/// `backups/pre_restore_<timestamp>.db` beforehand and put back if the restored data
+/// can't be migrated. Returns the safety copy's path
+#[command]
+pub async fn restore_backup(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    backup_path: String,
+) -> Result<String, CommandError> {
+    let backup = PathBuf::from(&backup_path);
+    validate_backup(&backup).await?;
+
+    let dir = backups_dir(&app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| CommandError::io("Failed to create backups directory", e))?;
+    let safety_path = dir.join(format!("pre_restore_{}.db", chrono::Utc::now().timestamp_millis()));
+
+    write_pending_snapshots(&state, None).await?;
+    let pool = state.writer();
+    let assets_setting = configured_assets_root(&state.pool()).await?;
+    sqlx::query("VACUUM INTO ?")
+        .bind(safety_path.to_string_lossy().to_string())
+        .execute(&pool)
+        .await
+        .map_err(|e| CommandError::db("Failed to save current database", e))?;
+
+    copy_database_into(backup, state.path()).await?;
+    // Snapshots buffered meanwhile would write the pre-restore canvas over the restored board
+    state.snapshots().discard();
+
+    // Older backups are brought up to the current schema. The assets folder setting
+    // is kept, as the files haven't moved back with the database
+    let restored = match migrations::run_migrations(&pool).await {
+        Ok(()) => save_assets_root(&pool, assets_setting.as_deref()).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = restored {
+        log::warn!("Restore failed, putting back the previous database: {}", e);
+        copy_database_into(safety_path.clone(), state.path())
+            .await
+            .map_err(|err| CommandError::Db(format!("{} (and failed to put back the previous database: {})", e, err)))?;
+        return Err(e);
+    }
+
+    log::info!("Restored database from backup: {}", backup_path);
+    Ok(safety_path.to_string_lossy().to_string())
+}
+
+// ============================================
+// Library Export
+// ============================================
+
+/// Entry names inside a library archive
+const LIBRARY_MANIFEST: &str = "library.json";
+const LIBRARY_DATABASE: &str = "notly.db";
+const LIBRARY_ASSETS_PREFIX: &str = "assets/";
+
+/// Bumped when the layout of library archives changes
+const LIBRARY_FORMAT_VERSION: i64 = 1;
+
+/// Describes a library archive; written as its first entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryManifest {
+    pub format_version: i64,
+    pub schema_version: i64,
+    pub exported_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryExport {
+    pub path: String,
+    pub size: u64,
+}
+
+/// Copy the live database to `target` with SQLite's online backup API
+fn snapshot_database(db_path: &Path, target: &Path) -> Result<(), CommandError> {
+    rusqlite::Connection::open(db_path)
+        .and_then(|live| live.backup(rusqlite::DatabaseName::Main, target, None))
+        .map_err(|e| CommandError::Db(format!("Failed to snapshot database: {}", e)))
+}
+
+/// Write the manifest, the database snapshot and every asset (except `temp/`) into a zip
+/// Files are streamed into the archive rather than read into memory
+fn write_library_archive(
+    target: &Path,
+    manifest: &LibraryManifest,
+    database: &Path,
+    assets_dir: &Path,
+) -> Result<(), CommandError> {
+    use std::io::Write;
+
+    let mut files = Vec::new();
+    if assets_dir.exists() {
+        collect_asset_files(assets_dir, "", &mut files)
+            .map_err(|e| CommandError::io("Failed to list assets", e))?;
+    }
+    files.retain(|path| !path.starts_with("temp/"));
+
+    let archive = std::fs::File::create(target)
+        .map_err(|e| CommandError::io("Failed to create library archive", e))?;
+    let mut zip = zip::ZipWriter::new(archive);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let manifest = serde_json::to_string_pretty(manifest)
+        .map_err(|e| CommandError::internal("Failed to encode library manifest", e))?;
+    zip.start_file(LIBRARY_MANIFEST, options)
+        .and_then(|_| zip.write_all(manifest.as_bytes()).map_err(Into::into))
+        .map_err(|e| CommandError::internal("Failed to write library archive", e))?;
+
+    let entries = std::iter::once((LIBRARY_DATABASE.to_string(), database.to_path_buf())).chain(
+        files
+            .iter()
+            .map(|relative_path| (format!("{}{}", LIBRARY_ASSETS_PREFIX, relative_path), assets_dir.join(relative_path))),
+    );
+    for (name, path) in entries {
+        let mut file = std::fs::File::open(&path)
+            .map_err(|e| CommandError::io(&format!("Failed to read {}", name), e))?;
+        let size = file
+            .metadata()
+            .map_err(|e| CommandError::io(&format!("Failed to read {}", name), e))?
+            .len();
+        zip.start_file(name.as_str(), options.large_file(size >= u32::MAX as u64))
+            .map_err(|e| CommandError::internal("Failed to write library archive", e))?;
+        std::io::copy(&mut file, &mut zip)
+            .map_err(|e| CommandError::io("Failed to write library archive", e))?;
+    }
+
+    zip.finish()
+        .map_err(|e| CommandError::internal("Failed to write library archive", e))?;
+    Ok(())
+}
+
+/// Export the whole library (database and assets folder) as one zip at `out_path`
+/// Buffered canvas snapshots are written first and the database is copied with the
+/// online backup API, so the archive is consistent while the app keeps running.
+/// A failed export leaves no partial archive behind
+#[command]
+pub async fn export_library(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    out_path: String,
+) -> Result<LibraryExport, CommandError> {
+    write_pending_snapshots(&state, None).await?;
+
+    let app_data_dir = app.path()
+        .app_data_dir()
+        .map_err(|e| CommandError::internal("Failed to get app data dir", e))?;
+    let temp_dir = app_data_dir.join("temp");
+    std::fs::create_dir_all(&temp_dir)
+        .map_err(|e| CommandError::io("Failed to create temp directory", e))?;
+
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let manifest = LibraryManifest {
+        format_version: LIBRARY_FORMAT_VERSION,
+        schema_version: migrations::latest_version(),
+        exported_at: timestamp,
+    };
+    let database = temp_dir.join(format!("library_export_{}.db", timestamp));
+    let assets_dir = assets_root(&app, &state).await?;
+    let target = PathBuf::from(&out_path);
+    let db_path = state.path();
+
+    let result = tauri::async_runtime::spawn_blocking({
+        let database = database.clone();
+        let target = target.clone();
+        move || {
+            snapshot_database(&db_path, &database)?;
+            write_library_archive(&target, &manifest, &database, &assets_dir)
+        }
+    })
+    .await
+    .map_err(|e| CommandError::internal("Failed to export library", e))
+    .and_then(|result| result);
+
+    let _ = std::fs::remove_file(&database);
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&target);
+        return Err(e);
+    }
+
+    let size = std::fs::metadata(&target)
+        .map_err(|e| CommandError::io("Failed to read library archive", e))?
+        .len();
+    log::info!("Exported library to {} ({} bytes)", out_path, size);
+    Ok(LibraryExport { path: out_path, size })
+}
+
+// ============================================
+// Library Import
+// ============================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryImport {
+    pub projects: i64,
+    pub boards: i64,
+    pub cards: i64,
+    /// Backup of the previous library, taken in "replace" mode
+    pub backup_path: Option<String>,
+}
+
+/// Unpack a library archive into `dir` after checking its manifest and database entry
+/// Entries are streamed to disk; names that would escape `dir` are rejected
+fn extract_library(zip_path: &Path, dir: &Path) -> Result<LibraryManifest, CommandError> {
+    use std::io::Read;
+
+    let file = std::fs::File::open(zip_path)
+        .map_err(|e| CommandError::io("Failed to open library archive", e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| CommandError::Validation(format!("Not a library archive: {}", e)))?;
+
+    let mut manifest = String::new();
+    archive
+        .by_name(LIBRARY_MANIFEST)
+        .map_err(|_| CommandError::Validation(format!("Not a library archive: missing {}", LIBRARY_MANIFEST)))?
+        .read_to_string(&mut manifest)
+        .map_err(|e| CommandError::io("Failed to read library manifest", e))?;
+    let manifest: LibraryManifest = serde_json::from_str(&manifest)
+        .map_err(|e| CommandError::Validation(format!("Invalid library manifest: {}", e)))?;
+    if manifest.format_version > LIBRARY_FORMAT_VERSION {
+        return Err(CommandError::Validation(format!(
+            "Library format version {} is newer than this app supports ({})",
+            manifest.format_version, LIBRARY_FORMAT_VERSION
+        )));
+    }
+    if archive.index_for_name(LIBRARY_DATABASE).is_none() {
+        return Err(CommandError::Validation(format!("Not a library archive: missing {}", LIBRARY_DATABASE)));
+    }
+
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| CommandError::internal("Failed to read library archive", e))?;
+        if entry.is_dir() || entry.name() == LIBRARY_MANIFEST {
+            continue;
+        }
+        let name = entry
+            .enclosed_name()
+            .ok_or_else(|| CommandError::Validation(format!("Invalid path in library archive: {}", entry.name())))?;
+        let target = dir.join(name);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| CommandError::io("Failed to create import folder", e))?;
+        }
+        std::fs::File::create(&target)
+            .and_then(|mut file| std::io::copy(&mut entry, &mut file))
+            .map_err(|e| CommandError::io(&format!("Failed to extract {}", target.display()), e))?;
+    }
+    Ok(manifest)
+}
+
+/// Totals of the live library, as reported after a replace
+async fn count_library(pool: &sqlx::SqlitePool) -> Result<(i64, i64, i64), CommandError> {
+    sqlx::query_as(
+        "SELECT (SELECT COUNT(*) FROM projects), (SELECT COUNT(*) FROM boards),
+                (SELECT COUNT(*) FROM cards WHERE deleted_at IS NULL)",
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| CommandError::db("Failed to count library", e))
+}
+
+/// Swap the extracted library in for the live one, after backing the live one up
+async fn replace_library(
+    app: &tauri::AppHandle,
+    state: State<'_, AppState>,
+    extracted: &Path,
+) -> Result<LibraryImport, CommandError> {
+    let backup_path = create_backup(app.clone(), state.clone(), true, None).await?;
+
+    let live_assets = assets_root(app, &state).await?;
+    let assets_setting = configured_assets_root(&state.pool()).await?;
+    let new_assets = extracted.join("assets");
+    let previous_assets = extracted.join("assets_previous");
+    std::fs::create_dir_all(&new_assets)
+        .map_err(|e| CommandError::io("Failed to create import folder", e))?;
+
+    // Renames, so the swap is quick and easy to undo; an assets folder set on
+    // another volume than the app data folder can't be swapped this way
+    if live_assets.exists() {
+        std::fs::rename(&live_assets, &previous_assets)
+            .map_err(|e| CommandError::io("Failed to move current assets aside", e))?;
+    }
+    let restore_assets = || {
+        let _ = std::fs::remove_dir_all(&live_assets);
+        if previous_assets.exists() {
+            let _ = std::fs::rename(&previous_assets, &live_assets);
+        }
+    };
+    if let Err(e) = std::fs::rename(&new_assets, &live_assets) {
+        restore_assets();
+        return Err(CommandError::io("Failed to move imported assets into place", e));
+    }
+
+    let pool = state.writer();
+    let copied = copy_database_into(extracted.join(LIBRARY_DATABASE), state.path()).await;
+    // Snapshots buffered meanwhile were meant for the library being replaced
+    state.snapshots().discard();
+    let restored = match copied {
+        Ok(()) => match migrations::run_migrations(&pool).await {
+            // The imported database may name the exporting machine's assets folder
+            Ok(()) => save_assets_root(&pool, assets_setting.as_deref()).await,
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    };
+    if let Err(e) = restored {
+        log::warn!("Library import failed, putting back the previous library: {}", e);
+        restore_assets();
+        copy_database_into(PathBuf::from(&backup_path), state.path())
+            .await
+            .map_err(|err| CommandError::Db(format!("{} (and failed to put back the previous database: {})", e, err)))?;
+        return Err(e);
+    }
+
+    let (projects, boards, cards) = count_library(&pool).await?;
+    Ok(LibraryImport { projects, boards, cards, backup_path: Some(backup_path) })
+}
+
+/// Bundle every project of an extracted library, copying external assets in with the rest
+async fn collect_library_bundles(
+    source: &sqlx::SqlitePool,
+    extracted_assets: &Path,
+) -> Result<Vec<ProjectBundle>, CommandError> {
+    let project_ids: Vec<String> = sqlx::query_scalar("SELECT id FROM projects ORDER BY created_at ASC")
+        .fetch_all(source)
+        .await
+        .map_err(|e| CommandError::db("Failed to read imported projects", e))?;
+
+    let mut bundles = Vec::with_capacity(project_ids.len());
+    for project_id in project_ids {
+        let (bundle, files) = build_project_bundle(source, &project_id, extracted_assets, "assets").await?;
+        copy_bundle_assets(&files, extracted_assets)?;
+        bundles.push(bundle);
+    }
+    Ok(bundles)
+}
+
+/// Add every project of the extracted library as a new project, in one transaction
+async fn merge_library(
+    app: &tauri::AppHandle,
+    state: State<'_, AppState>,
+    extracted: &Path,
+) -> Result<LibraryImport, CommandError> {
+    let extracted_assets = extracted.join("assets");
+
+    // The extracted copy may come from an older app version, so bring it up to date first
+    let source = crate::db::connect(&extracted.join(LIBRARY_DATABASE)).await?;
+    let bundles = collect_library_bundles(&source.pool, &extracted_assets).await;
+    source.close().await;
+    let bundles = bundles?;
+
+    let assets_dir = assets_root(app, &state).await?;
+
+    let mut imported = LibraryImport { projects: 0, boards: 0, cards: 0, backup_path: None };
+    let mut written = Vec::new();
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let mut result = Ok(());
+    for bundle in bundles {
+        let (boards, cards) = (bundle.boards.len() as i64, bundle.cards.len() as i64);
+        if let Err(e) = import_bundle(&mut tx, bundle, extracted, &assets_dir, &mut written).await {
+            result = Err(e);
+            break;
+        }
+        imported.projects += 1;
+        imported.boards += boards;
+        imported.cards += cards;
+    }
+    let result = match result {
+        Ok(()) => tx.commit().await.map_err(|e| CommandError::db("Failed to commit transaction", e)),
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        for path in &written {
+            if let Err(err) = std::fs::remove_file(path) {
+                log::warn!("Failed to remove imported asset {:?}: {}", path, err);
+            }
+        }
+        return Err(e);
+    }
+    Ok(imported)
+}
+
+/// Import a library archive written by `export_library`
+/// "replace" swaps the whole library (database and assets) for the archive's, backing the
+/// current one up first (see `create_backup`) and putting it back if the swap fails.
+/// "merge" adds each archived project as a new project with fresh ids, like `import_project`.
+/// The archive's manifest and database are checked before anything is touched
+#[command]
+pub async fn import_library(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    zip_path: String,
+    mode: String,
+) -> Result<LibraryImport, CommandError> {
+    if !matches!(mode.as_str(), "replace" | "merge") {
+        return Err(CommandError::Validation(format!("Unknown import mode: {}", mode)));
+    }
+    write_pending_snapshots(&state, None).await?;
+
+    let extracted = app.path()
+        .app_data_dir()
+        .map_err(|e| CommandError::internal("Failed to get app data dir", e))?
+        .join("temp")
+        .join(format!("library_import_{}", chrono::Utc::now().timestamp_millis()));
+    std::fs::create_dir_all(&extracted)
+        .map_err(|e| CommandError::io("Failed to create import folder", e))?;
+
+    let result = async {
+        let zip_path = PathBuf::from(&zip_path);
+        let target = extracted.clone();
+        tauri::async_runtime::spawn_blocking(move || extract_library(&zip_path, &target))
+            .await
+            .map_err(|e| CommandError::internal("Failed to extract library", e))??;
+        validate_backup(&extracted.join(LIBRARY_DATABASE)).await?;
+
+        if mode == "replace" {
+            replace_library(&app, state, &extracted).await
+        } else {
+            merge_library(&app, state, &extracted).await
+        }
+    }
+    .await;
+
+    if let Err(e) = std::fs::remove_dir_all(&extracted) {
+        log::warn!("Failed to remove import folder {:?}: {}", extracted, e);
+    }
+
+    let imported = result?;
+    log::info!(
+        "Imported library from {} ({}): {} projects, {} boards, {} cards",
+        zip_path,
+        mode,
+        imported.projects,
+        imported.boards,
+        imported.cards
+    );
+    Ok(imported)
+}
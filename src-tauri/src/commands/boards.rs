@@ -0,0 +1,1046 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{command, Manager, State};
+
+use super::cards::{keep_card_updated_at, Card, CARD_COLUMNS};
+use super::content::card_plaintext;
+use super::database::refresh_asset_refs;
+use super::search;
+use crate::db::AppState;
+use crate::error::CommandError;
+use crate::validate;
+
+// ============================================
+// Board Commands
+// ============================================
+
+pub(crate) const BOARD_COLUMNS: &str =
+    "id, project_id, parent_board_id, title, position, snapshot_updated_at, created_at, updated_at";
+
+/// Board metadata; the tldraw snapshot is loaded separately since it can be large
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Board {
+    pub id: String,
+    pub project_id: String,
+    pub parent_board_id: Option<String>,
+    pub title: String,
+    pub position: i64,
+    pub snapshot_updated_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Create a new board inside a project
+/// When no position is given the board is appended after its siblings
+#[command]
+pub async fn create_board(
+    state: State<'_, AppState>,
+    id: String,
+    project_id: String,
+    parent_board_id: Option<String>,
+    title: String,
+    position: Option<i64>,
+) -> Result<Board, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let title = validate::title("title", &title)?;
+
+    if let Some(parent_id) = &parent_board_id {
+        let parent_exists = sqlx::query("SELECT 1 FROM boards WHERE id = ? AND project_id = ?")
+            .bind(parent_id)
+            .bind(&project_id)
+            .fetch_optional(&state.pool())
+            .await
+            .map_err(|e| CommandError::db("Failed to look up parent board", e))?
+            .is_some();
+        if !parent_exists {
+            return Err(CommandError::NotFound(format!("parent board not found: {}", parent_id)));
+        }
+    }
+
+    let position = match position {
+        Some(position) => position,
+        None => sqlx::query_scalar::<_, i64>(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM boards
+             WHERE project_id = ? AND parent_board_id IS ?",
+        )
+        .bind(&project_id)
+        .bind(&parent_board_id)
+        .fetch_one(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to compute board position", e))?,
+    };
+
+    let board = sqlx::query_as::<_, Board>(&format!(
+        "INSERT INTO boards (id, project_id, parent_board_id, title, position, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?) RETURNING {}",
+        BOARD_COLUMNS
+    ))
+    .bind(&id)
+    .bind(&project_id)
+    .bind(&parent_board_id)
+    .bind(&title)
+    .bind(position)
+    .bind(now)
+    .bind(now)
+    .fetch_one(&state.writer())
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+            CommandError::Conflict(format!("board already exists: {}", id))
+        }
+        sqlx::Error::Database(ref db_err) if db_err.is_foreign_key_violation() => {
+            CommandError::NotFound(format!("project not found: {}", project_id))
+        }
+        _ => CommandError::db("Failed to create board", e),
+    })?;
+
+    log::info!("Created board: {} in project {}", board.id, board.project_id);
+    Ok(board)
+}
+
+/// Reads a present field as `Some`, so `null` becomes `Some(None)`; with `default`
+/// a missing field stays `None`
+fn double_option<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Changes for `update_board`; fields left out are not touched
+/// `parentBoardId: null` moves the board to the top level, while leaving the field
+/// out keeps its parent. Command arguments can't tell the two apart, hence the struct
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardUpdate {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub position: Option<i64>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub parent_board_id: Option<Option<String>>,
+}
+
+/// Rename, reposition or move a board within its project
+/// The new parent must be in the same project and not the board itself or one of its
+/// descendants. A board moved without a `position` is appended after its new siblings
+#[command]
+pub async fn update_board(
+    state: State<'_, AppState>,
+    id: String,
+    changes: BoardUpdate,
+) -> Result<Board, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let title = validate::optional_title("title", changes.title)?;
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let project_id: String = sqlx::query_scalar("SELECT project_id FROM boards WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to get board", e))?
+        .ok_or_else(|| CommandError::NotFound(format!("board not found: {}", id)))?;
+
+    let mut position = changes.position;
+    if let Some(parent_board_id) = &changes.parent_board_id {
+        if let Some(parent_id) = parent_board_id {
+            let parent_exists = sqlx::query("SELECT 1 FROM boards WHERE id = ? AND project_id = ?")
+                .bind(parent_id)
+                .bind(&project_id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| CommandError::db("Failed to look up parent board", e))?
+                .is_some();
+            if !parent_exists {
+                return Err(CommandError::NotFound(format!("parent board not found: {}", parent_id)));
+            }
+
+            // UNION (not UNION ALL) so an existing parent pointer loop can't recurse forever
+            let creates_cycle = sqlx::query(
+                "WITH RECURSIVE ancestors(id) AS (
+                     SELECT ?1
+                     UNION
+                     SELECT b.parent_board_id FROM boards b JOIN ancestors a ON b.id = a.id
+                     WHERE b.parent_board_id IS NOT NULL
+                 )
+                 SELECT 1 FROM ancestors WHERE id = ?2",
+            )
+            .bind(parent_id)
+            .bind(&id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to check board ancestors", e))?
+            .is_some();
+            if creates_cycle {
+                return Err(CommandError::Validation(format!(
+                    "board {} can't be moved inside itself or one of its child boards",
+                    id
+                )));
+            }
+        }
+
+        if position.is_none() {
+            position = Some(
+                sqlx::query_scalar::<_, i64>(
+                    "SELECT COALESCE(MAX(position) + 1, 0) FROM boards
+                     WHERE project_id = ? AND parent_board_id IS ? AND id != ?",
+                )
+                .bind(&project_id)
+                .bind(parent_board_id)
+                .bind(&id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| CommandError::db("Failed to compute board position", e))?,
+            );
+        }
+    }
+
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new("UPDATE boards SET updated_at = ");
+    query.push_bind(now);
+    if let Some(title) = &title {
+        query.push(", title = ").push_bind(title);
+    }
+    if let Some(position) = position {
+        query.push(", position = ").push_bind(position);
+    }
+    if let Some(parent_board_id) = changes.parent_board_id {
+        query.push(", parent_board_id = ").push_bind(parent_board_id);
+    }
+    query.push(" WHERE id = ").push_bind(&id);
+    query.push(format!(" RETURNING {}", BOARD_COLUMNS));
+
+    let board = query
+        .build_query_as::<Board>()
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to update board", e))?;
+
+    if title.is_some() {
+        search::index_entity(&mut tx, "board", &board.id, &board.title, "", "")
+            .await
+            .map_err(|e| CommandError::db("Failed to index board", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Updated board: {}", board.id);
+    Ok(board)
+}
+
+/// Get the boards of a project ordered by position
+/// With no `parent_board_id` only top-level boards are returned,
+/// otherwise only the direct children of that board
+#[command]
+pub async fn get_boards(
+    state: State<'_, AppState>,
+    project_id: String,
+    parent_board_id: Option<String>,
+) -> Result<Vec<Board>, CommandError> {
+    let boards = sqlx::query_as::<_, Board>(&format!(
+        "SELECT {} FROM boards
+         WHERE project_id = ? AND parent_board_id IS ?
+         ORDER BY position ASC",
+        BOARD_COLUMNS
+    ))
+    .bind(&project_id)
+    .bind(&parent_board_id)
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get boards", e))?;
+
+    Ok(boards)
+}
+
+/// A board with its child boards nested inside it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardNode {
+    #[serde(flatten)]
+    pub board: Board,
+    pub children: Vec<BoardNode>,
+}
+
+/// Nest `board` and everything below it; boards already in `placed` are skipped,
+/// so a parent pointer loop can't recurse forever
+fn build_board_node(board: Board, children_of: &mut HashMap<String, Vec<Board>>, placed: &mut HashSet<String>) -> BoardNode {
+    placed.insert(board.id.clone());
+    let mut children = Vec::new();
+    for child in children_of.remove(&board.id).unwrap_or_default() {
+        if !placed.contains(&child.id) {
+            children.push(build_board_node(child, children_of, placed));
+        }
+    }
+    BoardNode { board, children }
+}
+
+/// Get every board of a project as a tree, siblings ordered by position
+/// Boards whose parent is missing or outside the project are returned at the top level,
+/// as is the first board of any parent pointer loop, so no board is left out
+#[command]
+pub async fn get_boards_tree(state: State<'_, AppState>, project_id: String) -> Result<Vec<BoardNode>, CommandError> {
+    let boards = sqlx::query_as::<_, Board>(&format!(
+        "SELECT {} FROM boards WHERE project_id = ? ORDER BY position ASC, created_at ASC",
+        BOARD_COLUMNS
+    ))
+    .bind(&project_id)
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get boards", e))?;
+
+    let ids: HashSet<String> = boards.iter().map(|board| board.id.clone()).collect();
+    let mut roots = Vec::new();
+    let mut children_of: HashMap<String, Vec<Board>> = HashMap::new();
+    for board in &boards {
+        match &board.parent_board_id {
+            Some(parent_id) if parent_id != &board.id && ids.contains(parent_id) => {
+                children_of.entry(parent_id.clone()).or_default().push(board.clone())
+            }
+            _ => roots.push(board.clone()),
+        }
+    }
+
+    let mut placed = HashSet::new();
+    let mut tree = Vec::new();
+    for root in roots {
+        tree.push(build_board_node(root, &mut children_of, &mut placed));
+    }
+    // Whatever is left sits on a parent pointer loop; break each loop at its first board
+    for board in boards {
+        if !placed.contains(&board.id) {
+            tree.push(build_board_node(board, &mut children_of, &mut placed));
+        }
+    }
+
+    Ok(tree)
+}
+
+/// Get the chain of boards from the top-level board down to `board_id`, for breadcrumbs
+/// Each board is visited once, so a corrupted parent pointer that loops ends the chain
+#[command]
+pub async fn get_board_ancestors(state: State<'_, AppState>, board_id: String) -> Result<Vec<Board>, CommandError> {
+    let boards = sqlx::query_as::<_, Board>(&format!(
+        "WITH RECURSIVE ancestors(id, parent_id, depth, visited) AS (
+             SELECT id, parent_board_id, 0, ',' || id || ',' FROM boards WHERE id = ?
+             UNION ALL
+             SELECT b.id, b.parent_board_id, a.depth + 1, a.visited || b.id || ','
+             FROM boards b JOIN ancestors a ON b.id = a.parent_id
+             WHERE instr(a.visited, ',' || b.id || ',') = 0
+         )
+         SELECT {} FROM boards JOIN ancestors USING (id)
+         ORDER BY ancestors.depth DESC",
+        BOARD_COLUMNS
+    ))
+    .bind(&board_id)
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get board ancestors", e))?;
+
+    if boards.is_empty() {
+        return Err(CommandError::NotFound(format!("board not found: {}", board_id)));
+    }
+    Ok(boards)
+}
+
+/// Delete a board with its canvas snapshot, cards and search index entries
+/// With `recursive` the whole subtree of nested boards goes too; otherwise a board
+/// that still has child boards is rejected. Cards also placed on a board outside the
+/// deleted boards are kept. Returns false if no board with this id existed
+#[command]
+pub async fn delete_board(state: State<'_, AppState>, board_id: String, recursive: bool) -> Result<bool, CommandError> {
+    // UNION (not UNION ALL) so a parent pointer loop can't recurse forever
+    const SUBTREE: &str = "WITH RECURSIVE subtree(id) AS (
+             SELECT id FROM boards WHERE id = ?1
+             UNION
+             SELECT b.id FROM boards b JOIN subtree s ON b.parent_board_id = s.id
+         ),
+         doomed_cards AS (
+             SELECT card_id FROM board_cards WHERE board_id IN subtree
+             EXCEPT
+             SELECT card_id FROM board_cards WHERE board_id NOT IN subtree
+         )";
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    if !recursive {
+        let has_children = sqlx::query("SELECT 1 FROM boards WHERE parent_board_id = ? LIMIT 1")
+            .bind(&board_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to get boards", e))?
+            .is_some();
+        if has_children {
+            return Err(CommandError::Conflict("board has child boards".to_string()));
+        }
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let tombstones = [
+        "SELECT 'card', card_id, ?2 FROM doomed_cards",
+        "SELECT 'board', id, ?2 FROM subtree",
+    ];
+    for select in tombstones {
+        sqlx::query(&format!(
+            "{} INSERT OR REPLACE INTO tombstones (entity_type, entity_id, deleted_at) {}",
+            SUBTREE, select
+        ))
+        .bind(&board_id)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to record deletion", e))?;
+    }
+
+    let statements = [
+        "DELETE FROM search_index WHERE entity_type = 'card' AND entity_id IN doomed_cards",
+        "DELETE FROM cards WHERE id IN doomed_cards",
+        "DELETE FROM search_index WHERE entity_type = 'board' AND entity_id IN subtree",
+    ];
+    for sql in statements {
+        sqlx::query(&format!("{} {}", SUBTREE, sql))
+            .bind(&board_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to delete board data", e))?;
+    }
+
+    let deleted = sqlx::query(&format!("{} DELETE FROM boards WHERE id IN subtree", SUBTREE))
+        .bind(&board_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to delete board", e))?
+        .rows_affected();
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    if deleted > 0 {
+        log::info!("Deleted board: {} ({} boards)", board_id, deleted);
+    } else {
+        log::warn!("Board not found: {}", board_id);
+    }
+    Ok(deleted > 0)
+}
+
+/// Persist a new board order in one transaction: each board's position becomes its index
+/// Fails without changing anything if an id does not belong to the project
+#[command]
+pub async fn reorder_boards(
+    state: State<'_, AppState>,
+    project_id: String,
+    ordered_ids: Vec<String>,
+) -> Result<(), CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let project_board_ids: HashSet<String> =
+        sqlx::query_scalar("SELECT id FROM boards WHERE project_id = ?")
+            .bind(&project_id)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to get boards", e))?
+            .into_iter()
+            .collect();
+
+    let unknown_ids: Vec<&str> = ordered_ids
+        .iter()
+        .filter(|id| !project_board_ids.contains(*id))
+        .map(String::as_str)
+        .collect();
+    if !unknown_ids.is_empty() {
+        return Err(CommandError::NotFound(format!(
+            "Boards not found in project {}: {}",
+            project_id,
+            unknown_ids.join(", ")
+        )));
+    }
+
+    for (index, board_id) in ordered_ids.iter().enumerate() {
+        sqlx::query("UPDATE boards SET position = ?, updated_at = ? WHERE id = ?")
+            .bind(index as i64)
+            .bind(now)
+            .bind(board_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to update board position", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Reordered {} boards in project {}", ordered_ids.len(), project_id);
+    Ok(())
+}
+
+/// A board row together with its stored snapshot blob
+#[derive(sqlx::FromRow)]
+struct BoardWithSnapshot {
+    #[sqlx(flatten)]
+    board: Board,
+    tldraw_snapshot: Option<Vec<u8>>,
+}
+
+/// Geometry of a card on a board
+#[derive(sqlx::FromRow)]
+struct CardPlacement {
+    card_id: String,
+    x: Option<f64>,
+    y: Option<f64>,
+    width: Option<f64>,
+    height: Option<f64>,
+}
+
+/// Deep-copy a board and all of its nested child boards within the same project
+/// Cards on the copied boards are duplicated with their tags and placements, and
+/// board/card ids inside the tldraw snapshots are remapped to the copies. The copy
+/// is appended after its siblings as `new_title` (default "<title> (copy)")
+#[command]
+pub async fn duplicate_board(
+    state: State<'_, AppState>,
+    board_id: String,
+    new_title: Option<String>,
+) -> Result<Board, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let new_title = new_title.map(|title| validate::title("title", &title)).transpose()?;
+    let new_id = || uuid::Uuid::new_v4().to_string();
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    // Parents come before their children so each copy's parent already exists
+    let boards = sqlx::query_as::<_, BoardWithSnapshot>(&format!(
+        "WITH RECURSIVE subtree(id, depth, visited) AS (
+             SELECT id, 0, ',' || id || ',' FROM boards WHERE id = ?
+             UNION ALL
+             SELECT b.id, s.depth + 1, s.visited || b.id || ','
+             FROM boards b JOIN subtree s ON b.parent_board_id = s.id
+             WHERE instr(s.visited, ',' || b.id || ',') = 0
+         )
+         SELECT {}, tldraw_snapshot FROM boards JOIN subtree USING (id)
+         ORDER BY subtree.depth, position",
+        BOARD_COLUMNS
+    ))
+    .bind(&board_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to get boards", e))?;
+    let Some(root) = boards.first().map(|b| b.board.clone()) else {
+        return Err(CommandError::NotFound(format!("board not found: {}", board_id)));
+    };
+
+    let board_ids: HashMap<String, String> =
+        boards.iter().map(|b| (b.board.id.clone(), new_id())).collect();
+    let subtree_ids: Vec<&str> = boards.iter().map(|b| b.board.id.as_str()).collect();
+
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new(format!(
+        "SELECT {} FROM cards WHERE deleted_at IS NULL AND id IN
+         (SELECT card_id FROM board_cards WHERE board_id IN (",
+        CARD_COLUMNS
+    ));
+    let mut separated = query.separated(", ");
+    for id in &subtree_ids {
+        separated.push_bind(*id);
+    }
+    query.push("))");
+    let cards = query
+        .build_query_as::<Card>()
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to get cards", e))?;
+    let card_ids: HashMap<String, String> =
+        cards.iter().map(|c| (c.id.clone(), new_id())).collect();
+
+    // Shapes refer to boards and cards by quoted id
+    let remap = |text: &str| {
+        let mut text = text.to_string();
+        for (old, new) in board_ids.iter().chain(card_ids.iter()) {
+            text = text.replace(&format!("\"{}\"", old), &format!("\"{}\"", new));
+        }
+        text
+    };
+
+    let root_position: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(MAX(position) + 1, 0) FROM boards
+         WHERE project_id = ? AND parent_board_id IS ?",
+    )
+    .bind(&root.project_id)
+    .bind(&root.parent_board_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to compute board position", e))?;
+
+    for BoardWithSnapshot { board, tldraw_snapshot } in &boards {
+        let is_root = board.id == root.id;
+        let title = match &new_title {
+            Some(title) if is_root => title.clone(),
+            None if is_root => format!("{} (copy)", board.title),
+            _ => board.title.clone(),
+        };
+        let parent_board_id = if is_root {
+            board.parent_board_id.clone()
+        } else {
+            board.parent_board_id.as_ref().map(|id| board_ids[id].clone())
+        };
+        let snapshot = tldraw_snapshot
+            .as_deref()
+            .map(|data| decompress_snapshot(data).and_then(|s| compress_snapshot(&remap(&s))))
+            .transpose()?;
+
+        sqlx::query(
+            "INSERT INTO boards (id, project_id, parent_board_id, title, position, tldraw_snapshot,
+                 snapshot_updated_at, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&board_ids[&board.id])
+        .bind(&board.project_id)
+        .bind(parent_board_id)
+        .bind(&title)
+        .bind(if is_root { root_position } else { board.position })
+        .bind(snapshot)
+        .bind(tldraw_snapshot.as_ref().map(|_| now))
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to copy board", e))?;
+        search::index_entity(&mut tx, "board", &board_ids[&board.id], &title, "", "")
+            .await
+            .map_err(|e| CommandError::db("Failed to index board", e))?;
+    }
+
+    for card in &cards {
+        let new_card_id = &card_ids[&card.id];
+        sqlx::query(&format!(
+            "INSERT INTO cards ({}, preview)
+             VALUES (?, ?, ?, ?, ?, ?, ?, NULL, ?, ?, ?, ?, (SELECT preview FROM cards WHERE id = ?))",
+            CARD_COLUMNS
+        ))
+        .bind(new_card_id)
+        .bind(&card.title)
+        .bind(&card.content)
+        .bind(&card.content_type)
+        .bind(&card.color)
+        .bind(card.is_hidden)
+        .bind(card.word_count)
+        .bind(now)
+        .bind(now)
+        .bind(&card.metadata)
+        .bind(card.is_pinned)
+        .bind(&card.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to copy card", e))?;
+
+        sqlx::query(
+            "INSERT INTO card_tags (card_id, tag_id, created_at)
+             SELECT ?, tag_id, ? FROM card_tags WHERE card_id = ?",
+        )
+        .bind(new_card_id)
+        .bind(now)
+        .bind(&card.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to copy card tags", e))?;
+        keep_card_updated_at(&mut tx, new_card_id, now).await?;
+        refresh_asset_refs(&mut tx, new_card_id).await?;
+
+        search::index_entity(
+            &mut tx,
+            "card",
+            new_card_id,
+            card.title.as_deref().unwrap_or_default(),
+            &card_plaintext(&card.content_type, &card.content),
+            "",
+        )
+        .await
+        .map_err(|e| CommandError::db("Failed to index card", e))?;
+    }
+
+    for (old_board_id, new_board_id) in &board_ids {
+        let placements = sqlx::query_as::<_, CardPlacement>(
+            "SELECT card_id, x, y, width, height FROM board_cards WHERE board_id = ?",
+        )
+        .bind(old_board_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to get board cards", e))?;
+        for placement in placements {
+            // Trashed cards were not copied
+            let Some(new_card_id) = card_ids.get(&placement.card_id) else { continue };
+            sqlx::query(
+                "INSERT INTO board_cards (board_id, card_id, x, y, width, height, created_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(new_board_id)
+            .bind(new_card_id)
+            .bind(placement.x)
+            .bind(placement.y)
+            .bind(placement.width)
+            .bind(placement.height)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to copy board card", e))?;
+        }
+    }
+
+    let copy = sqlx::query_as::<_, Board>(&format!("SELECT {} FROM boards WHERE id = ?", BOARD_COLUMNS))
+        .bind(&board_ids[&root.id])
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to get board", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!(
+        "Duplicated board {} as {} ({} boards, {} cards)",
+        board_id,
+        copy.id,
+        boards.len(),
+        cards.len()
+    );
+    Ok(copy)
+}
+
+// ============================================
+// Canvas Snapshot Commands
+// ============================================
+
+/// Header byte marking a gzip-compressed snapshot blob
+/// Rows without it are legacy uncompressed JSON text
+const SNAPSHOT_GZIP_HEADER: u8 = 0x01;
+
+pub(crate) fn compress_snapshot(snapshot: &str) -> Result<Vec<u8>, CommandError> {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(vec![SNAPSHOT_GZIP_HEADER], Compression::default());
+    encoder
+        .write_all(snapshot.as_bytes())
+        .and_then(|_| encoder.finish())
+        .map_err(|e| CommandError::io("Failed to compress snapshot", e))
+}
+
+pub(crate) fn decompress_snapshot(data: &[u8]) -> Result<String, CommandError> {
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    match data.split_first() {
+        Some((&SNAPSHOT_GZIP_HEADER, compressed)) => {
+            let mut snapshot = String::new();
+            GzDecoder::new(compressed)
+                .read_to_string(&mut snapshot)
+                .map_err(|e| CommandError::io("Failed to decompress snapshot", e))?;
+            Ok(snapshot)
+        }
+        _ => String::from_utf8(data.to_vec())
+            .map_err(|e| CommandError::internal("Invalid snapshot data", e)),
+    }
+}
+
+// ============================================
+// Snapshot Autosave
+// ============================================
+
+/// Snapshots of a board arriving this soon after its last write are held back
+/// and only the latest of them is written once the window has passed
+const SNAPSHOT_COALESCE_WINDOW: Duration = Duration::from_millis(500);
+
+/// Latest unwritten snapshot per board, so rapid autosaves coalesce into one write
+#[derive(Default)]
+pub struct SnapshotBuffer {
+    boards: Mutex<HashMap<String, SnapshotSlot>>,
+}
+
+#[derive(Default)]
+struct SnapshotSlot {
+    last_write: Option<Instant>,
+    pending: Option<PendingSnapshot>,
+}
+
+struct PendingSnapshot {
+    snapshot: String,
+    received_at: i64,
+}
+
+/// What `SnapshotBuffer::offer` decided for an incoming snapshot
+enum SnapshotOffer {
+    /// Write it now
+    Write(String),
+    /// Held back; start a timer that writes it after this delay
+    Schedule(Duration),
+    /// Replaced an already pending snapshot whose timer is running
+    Buffered,
+}
+
+impl SnapshotBuffer {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, SnapshotSlot>> {
+        self.boards.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn offer(&self, board_id: &str, snapshot: String, received_at: i64) -> SnapshotOffer {
+        let now = Instant::now();
+        let mut boards = self.lock();
+        let slot = boards.entry(board_id.to_string()).or_default();
+
+        match slot.last_write {
+            Some(last_write) if slot.pending.is_some() || now - last_write < SNAPSHOT_COALESCE_WINDOW => {
+                let had_pending = slot.pending.replace(PendingSnapshot { snapshot, received_at }).is_some();
+                if had_pending {
+                    SnapshotOffer::Buffered
+                } else {
+                    SnapshotOffer::Schedule(SNAPSHOT_COALESCE_WINDOW.saturating_sub(now - last_write))
+                }
+            }
+            _ => {
+                slot.last_write = Some(now);
+                SnapshotOffer::Write(snapshot)
+            }
+        }
+    }
+
+    /// The pending snapshot of a board, if any, without taking it
+    fn peek(&self, board_id: &str) -> Option<String> {
+        self.lock()
+            .get(board_id)
+            .and_then(|slot| slot.pending.as_ref())
+            .map(|pending| pending.snapshot.clone())
+    }
+
+    /// Take the pending snapshots of the given boards (all boards if None) for writing
+    fn take(&self, board_id: Option<&str>) -> Vec<(String, PendingSnapshot)> {
+        let now = Instant::now();
+        self.lock()
+            .iter_mut()
+            .filter(|(id, _)| board_id.map_or(true, |board_id| board_id == id.as_str()))
+            .filter_map(|(id, slot)| {
+                let pending = slot.pending.take()?;
+                slot.last_write = Some(now);
+                Some((id.clone(), pending))
+            })
+            .collect()
+    }
+
+    /// Drop every pending snapshot unwritten, e.g. once the database they were meant
+    /// for has been replaced. Returns how many were dropped
+    pub(crate) fn discard(&self) -> usize {
+        let mut boards = self.lock();
+        let dropped = boards.values().filter(|slot| slot.pending.is_some()).count();
+        boards.clear();
+        dropped
+    }
+}
+
+/// Compress and store a snapshot; returns false if the board doesn't exist
+/// A snapshot never overwrites one that was received after it
+async fn write_snapshot(
+    pool: &sqlx::SqlitePool,
+    board_id: &str,
+    snapshot: &str,
+    received_at: i64,
+) -> Result<bool, CommandError> {
+    let compressed = compress_snapshot(snapshot)?;
+
+    let result = sqlx::query(
+        "UPDATE boards SET tldraw_snapshot = ?, snapshot_updated_at = ?, updated_at = ?
+         WHERE id = ? AND (snapshot_updated_at IS NULL OR snapshot_updated_at <= ?)",
+    )
+    .bind(&compressed)
+    .bind(received_at)
+    .bind(chrono::Utc::now().timestamp_millis())
+    .bind(board_id)
+    .bind(received_at)
+    .execute(pool)
+    .await
+    .map_err(|e| CommandError::db("Failed to save canvas snapshot", e))?;
+
+    if result.rows_affected() == 0 {
+        return sqlx::query("SELECT 1 FROM boards WHERE id = ?")
+            .bind(board_id)
+            .fetch_optional(pool)
+            .await
+            .map(|row| row.is_some())
+            .map_err(|e| CommandError::db("Failed to save canvas snapshot", e));
+    }
+
+    log::info!(
+        "Saved canvas snapshot for board {} ({} bytes, {} compressed)",
+        board_id,
+        snapshot.len(),
+        compressed.len()
+    );
+    Ok(true)
+}
+
+/// Write the pending snapshots of the given boards (all boards if None)
+/// Returns how many were written
+pub(crate) async fn write_pending_snapshots(state: &AppState, board_id: Option<&str>) -> Result<usize, CommandError> {
+    let pending = state.snapshots().take(board_id);
+    let mut written = 0;
+    for (board_id, snapshot) in pending {
+        if write_snapshot(&state.writer(), &board_id, &snapshot.snapshot, snapshot.received_at).await? {
+            written += 1;
+        } else {
+            log::warn!("Dropped canvas snapshot for missing board: {}", board_id);
+        }
+    }
+    Ok(written)
+}
+
+/// Save the tldraw snapshot JSON for a board (stored gzip-compressed)
+/// Snapshots arriving within `SNAPSHOT_COALESCE_WINDOW` of the board's last write are
+/// buffered and only the latest is written when the window ends. A buffered snapshot
+/// for a board that was deleted meanwhile is dropped with a warning
+#[command]
+pub async fn save_canvas_snapshot(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    board_id: String,
+    snapshot: String,
+) -> Result<bool, CommandError> {
+    let received_at = chrono::Utc::now().timestamp_millis();
+
+    // Checked up front so unknown boards fail even when the snapshot would be buffered
+    let board_exists = sqlx::query("SELECT 1 FROM boards WHERE id = ?")
+        .bind(&board_id)
+        .fetch_optional(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to look up board", e))?
+        .is_some();
+    if !board_exists {
+        return Err(CommandError::NotFound("board not found".to_string()));
+    }
+
+    match state.snapshots().offer(&board_id, snapshot, received_at) {
+        SnapshotOffer::Write(snapshot) => {
+            if !write_snapshot(&state.writer(), &board_id, &snapshot, received_at).await? {
+                return Err(CommandError::NotFound("board not found".to_string()));
+            }
+        }
+        SnapshotOffer::Schedule(delay) => {
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(delay).await;
+                let state = app.state::<AppState>();
+                if let Err(e) = write_pending_snapshots(&state, Some(&board_id)).await {
+                    log::warn!("Failed to write buffered snapshot for board {}: {}", board_id, e);
+                }
+            });
+        }
+        SnapshotOffer::Buffered => {}
+    }
+    Ok(true)
+}
+
+/// Write every buffered canvas snapshot now, e.g. before the app exits
+/// Returns how many snapshots were written
+#[command]
+pub async fn flush_pending_snapshots(state: State<'_, AppState>) -> Result<usize, CommandError> {
+    let written = write_pending_snapshots(&state, None).await?;
+    if written > 0 {
+        log::info!("Flushed {} pending canvas snapshots", written);
+    }
+    Ok(written)
+}
+
+/// Load the tldraw snapshot JSON for a board
+/// Returns None for unknown boards or boards that were never saved
+#[command]
+pub async fn load_canvas_snapshot(
+    state: State<'_, AppState>,
+    board_id: String,
+) -> Result<Option<String>, CommandError> {
+    // A snapshot still waiting to be written is newer than the stored one
+    if let Some(snapshot) = state.snapshots().peek(&board_id) {
+        return Ok(Some(snapshot));
+    }
+
+    let data = sqlx::query_scalar::<_, Option<Vec<u8>>>(
+        "SELECT tldraw_snapshot FROM boards WHERE id = ?",
+    )
+    .bind(&board_id)
+    .fetch_optional(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to load canvas snapshot", e))?
+    .flatten();
+
+    data.map(|data| decompress_snapshot(&data)).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tauri::Manager;
+
+    use super::*;
+    use crate::test_support::{seed_card, test_app};
+
+    #[tokio::test]
+    async fn duplicate_board_stops_at_parent_loops() {
+        let (app, _dir) = test_app().await;
+        seed_card(&app, "c").await;
+        create_board(app.state(), "child".into(), "p".into(), Some("b".into()), "Child".into(), None)
+            .await
+            .unwrap();
+        // Corrupt the tree into b -> child -> b
+        sqlx::query("UPDATE boards SET parent_board_id = 'child' WHERE id = 'b'")
+            .execute(&app.state::<AppState>().writer())
+            .await
+            .unwrap();
+
+        let copy = tokio::time::timeout(Duration::from_secs(10), duplicate_board(app.state(), "b".into(), None))
+            .await
+            .expect("duplicate_board did not finish")
+            .unwrap();
+        let copies: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM boards WHERE id NOT IN ('b', 'child')")
+            .fetch_one(&app.state::<AppState>().pool())
+            .await
+            .unwrap();
+        assert_eq!(copy.title, "B (copy)");
+        assert_eq!(copies, 2);
+    }
+
+    #[test]
+    fn snapshot_buffer_discard_drops_pending_snapshots() {
+        let buffer = SnapshotBuffer::default();
+        assert!(matches!(buffer.offer("b", "first".into(), 1), SnapshotOffer::Write(_)));
+        assert!(matches!(buffer.offer("b", "second".into(), 2), SnapshotOffer::Schedule(_)));
+        assert_eq!(buffer.peek("b").as_deref(), Some("second"));
+
+        assert_eq!(buffer.discard(), 1);
+        assert_eq!(buffer.peek("b"), None);
+        assert!(buffer.take(None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn duplicate_board_validates_the_new_title() {
+        let (app, _dir) = test_app().await;
+        seed_card(&app, "c").await;
+
+        let result = duplicate_board(app.state(), "b".into(), Some("bad\0title".into())).await;
+        assert!(matches!(result, Err(CommandError::Validation(_))));
+    }
+}
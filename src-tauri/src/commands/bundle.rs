@@ -0,0 +1,663 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{command, State};
+
+use super::app_state::assets_root;
+use super::boards::{compress_snapshot, decompress_snapshot, Board, BOARD_COLUMNS};
+use super::cards::{keep_card_updated_at, Card, CARD_COLUMNS};
+use super::content::{card_plaintext, card_preview};
+use super::database::{collect_asset_files, import_asset_copy, refresh_asset_refs, resolve_asset_path};
+use super::search::{self, IndexEntity};
+use super::projects::{Project, PROJECT_COLUMNS};
+use super::tags::{Tag, TAG_COLUMNS};
+use crate::db::AppState;
+use crate::error::CommandError;
+
+// ============================================
+// Project Bundle Commands
+// ============================================
+
+/// Version of the bundle format written by `export_project`
+pub const BUNDLE_SCHEMA_VERSION: u32 = 1;
+
+/// A board together with its canvas snapshot JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleBoard {
+    #[serde(flatten)]
+    pub board: Board,
+    pub snapshot: Option<String>,
+}
+
+/// Placement of a card on a board
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleBoardCard {
+    pub board_id: String,
+    pub card_id: String,
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleCardTag {
+    pub card_id: String,
+    pub tag_id: String,
+}
+
+/// An asset file shipped next to the bundle
+/// `path` is relative to the bundle's assets folder; content refers to it as `<assetsDir>/<path>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleAsset {
+    pub path: String,
+    pub file_type: String,
+}
+
+/// Self-contained JSON export of one project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectBundle {
+    pub schema_version: u32,
+    pub exported_at: i64,
+    pub project: Project,
+    pub boards: Vec<BundleBoard>,
+    pub cards: Vec<Card>,
+    pub board_cards: Vec<BundleBoardCard>,
+    pub tags: Vec<Tag>,
+    pub card_tags: Vec<BundleCardTag>,
+    /// Sibling folder holding the assets, relative to the bundle file
+    pub assets_dir: String,
+    pub assets: Vec<BundleAsset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectExport {
+    pub path: String,
+    pub asset_count: usize,
+}
+
+/// Forms a path can take inside stored text: as-is, and JSON-escaped (backslashes on Windows)
+fn path_forms(path: &str) -> Vec<String> {
+    let escaped = serde_json::to_string(path).unwrap_or_default();
+    let escaped = escaped.trim_matches('"').to_string();
+    if escaped == path {
+        vec![path.to_string()]
+    } else {
+        vec![path.to_string(), escaped]
+    }
+}
+
+/// Replace every occurrence of each `(from, to)` path in `text`
+pub(crate) fn rewrite_paths(text: &str, replacements: &[(String, String)]) -> String {
+    let mut text = text.to_string();
+    for (from, to) in replacements {
+        for (from, to) in path_forms(from).into_iter().zip(path_forms(to)) {
+            text = text.replace(&from, &to);
+        }
+    }
+    text
+}
+
+/// Collect a project from `pool` as a bundle whose assets live in `assets_dir_name`
+/// `assets_dir` is the library folder the project's relative asset paths point into.
+/// Returns the bundle and, for each asset, its source file and path inside `assets_dir_name`
+pub(crate) async fn build_project_bundle(
+    pool: &sqlx::SqlitePool,
+    project_id: &str,
+    assets_dir: &Path,
+    assets_dir_name: &str,
+) -> Result<(ProjectBundle, Vec<(PathBuf, String)>), CommandError> {
+    const PROJECT_BOARDS: &str = "SELECT id FROM boards WHERE project_id = ?";
+
+    let project = sqlx::query_as::<_, Project>(&format!(
+        "SELECT {} FROM projects WHERE id = ?",
+        PROJECT_COLUMNS
+    ))
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| CommandError::db("Failed to get project", e))?
+    .ok_or_else(|| CommandError::NotFound(format!("project not found: {}", project_id)))?;
+
+    let boards = sqlx::query_as::<_, Board>(&format!(
+        "SELECT {} FROM boards WHERE project_id = ? ORDER BY position ASC",
+        BOARD_COLUMNS
+    ))
+    .bind(project_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| CommandError::db("Failed to get boards", e))?;
+
+    let mut bundle_boards = Vec::with_capacity(boards.len());
+    for board in boards {
+        let snapshot = sqlx::query_scalar::<_, Option<Vec<u8>>>(
+            "SELECT tldraw_snapshot FROM boards WHERE id = ?",
+        )
+        .bind(&board.id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| CommandError::db("Failed to load canvas snapshot", e))?
+        .map(|data| decompress_snapshot(&data))
+        .transpose()?;
+        bundle_boards.push(BundleBoard { board, snapshot });
+    }
+
+    let board_cards = sqlx::query_as::<_, BundleBoardCard>(&format!(
+        "SELECT bc.board_id, bc.card_id, bc.x, bc.y, bc.width, bc.height FROM board_cards bc
+         JOIN cards c ON c.id = bc.card_id
+         WHERE bc.board_id IN ({}) AND c.deleted_at IS NULL",
+        PROJECT_BOARDS
+    ))
+    .bind(project_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| CommandError::db("Failed to get board cards", e))?;
+
+    let project_cards = format!(
+        "SELECT card_id FROM board_cards WHERE board_id IN ({})",
+        PROJECT_BOARDS
+    );
+    let cards = sqlx::query_as::<_, Card>(&format!(
+        "SELECT {} FROM cards WHERE deleted_at IS NULL AND id IN ({}) ORDER BY created_at ASC",
+        CARD_COLUMNS, project_cards
+    ))
+    .bind(project_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| CommandError::db("Failed to get cards", e))?;
+
+    let card_tags = sqlx::query_as::<_, BundleCardTag>(&format!(
+        "SELECT ct.card_id, ct.tag_id FROM card_tags ct
+         JOIN cards c ON c.id = ct.card_id
+         WHERE c.deleted_at IS NULL AND ct.card_id IN ({})",
+        project_cards
+    ))
+    .bind(project_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| CommandError::db("Failed to get card tags", e))?;
+
+    let tags = sqlx::query_as::<_, Tag>(&format!(
+        "SELECT {} FROM tags WHERE id IN (
+             SELECT ct.tag_id FROM card_tags ct
+             JOIN cards c ON c.id = ct.card_id
+             WHERE c.deleted_at IS NULL AND ct.card_id IN ({})
+         ) ORDER BY position ASC, name ASC",
+        TAG_COLUMNS, project_cards
+    ))
+    .bind(project_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| CommandError::db("Failed to get tags", e))?;
+
+    // Work out which assets the project refers to
+    let mut corpus = [project.thumbnail_path.clone(), project.settings.clone()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    corpus.extend(bundle_boards.iter().filter_map(|b| b.snapshot.clone()));
+    for card in &cards {
+        corpus.push(card.content.clone());
+        corpus.extend(card.metadata.clone());
+    }
+    let corpus = corpus.join("\n");
+    let is_referenced = |path: &str| path_forms(path).iter().any(|form| corpus.contains(form));
+
+    // (source file, path as referenced, path inside the bundle assets folder, file type)
+    let mut referenced: Vec<(PathBuf, String, String, String)> = Vec::new();
+    let mut local_files = Vec::new();
+    if assets_dir.exists() {
+        collect_asset_files(assets_dir, "", &mut local_files)
+            .map_err(|e| CommandError::io("Failed to list assets", e))?;
+    }
+    for relative_path in local_files {
+        if relative_path.starts_with("thumbnails/") || !is_referenced(&relative_path) {
+            continue;
+        }
+        let file_type = match relative_path.split('/').next() {
+            Some("pdfs") => "pdf",
+            Some("images") => "image",
+            _ => "other",
+        };
+        referenced.push((
+            assets_dir.join(&relative_path),
+            relative_path.clone(),
+            relative_path,
+            file_type.to_string(),
+        ));
+    }
+
+    // Referenced (external) files travel with the bundle as well
+    let external: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT id, relative_path, file_type FROM assets WHERE import_mode = 'reference'",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| CommandError::db("Failed to get assets", e))?;
+    for (id, path, file_type) in external {
+        let source = PathBuf::from(&path);
+        if !is_referenced(&path) || !source.is_file() {
+            continue;
+        }
+        let name = source.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+        referenced.push((source.clone(), path, format!("external/{}_{}", id, name), file_type));
+    }
+
+    // Longest paths first so no path is rewritten through a shorter one it contains
+    referenced.sort_by_key(|asset| std::cmp::Reverse(asset.1.len()));
+    let replacements: Vec<(String, String)> = referenced
+        .iter()
+        .map(|(_, from, to, _)| (from.clone(), format!("{}/{}", assets_dir_name, to)))
+        .collect();
+
+    for board in &mut bundle_boards {
+        board.snapshot = board.snapshot.as_deref().map(|s| rewrite_paths(s, &replacements));
+    }
+    let cards = cards
+        .into_iter()
+        .map(|mut card| {
+            card.content = rewrite_paths(&card.content, &replacements);
+            card.metadata = card.metadata.as_deref().map(|m| rewrite_paths(m, &replacements));
+            card
+        })
+        .collect();
+    let mut project = project;
+    project.thumbnail_path = project.thumbnail_path.as_deref().map(|p| rewrite_paths(p, &replacements));
+    project.settings = project.settings.as_deref().map(|s| rewrite_paths(s, &replacements));
+
+    let bundle = ProjectBundle {
+        schema_version: BUNDLE_SCHEMA_VERSION,
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        project,
+        boards: bundle_boards,
+        cards,
+        board_cards,
+        tags,
+        card_tags,
+        assets_dir: assets_dir_name.to_string(),
+        assets: referenced
+            .iter()
+            .map(|(_, _, path, file_type)| BundleAsset { path: path.clone(), file_type: file_type.clone() })
+            .collect(),
+    };
+    let files = referenced.into_iter().map(|(source, _, target, _)| (source, target)).collect();
+
+    Ok((bundle, files))
+}
+
+/// Export a project (boards, cards, tags and canvas snapshots) to a JSON bundle at `out_path`
+/// Assets the project refers to are copied into a sibling `<name>_assets` folder and
+/// references to them are rewritten relative to the bundle file
+#[command]
+pub async fn export_project(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+    out_path: String,
+) -> Result<ProjectExport, CommandError> {
+    let out_path = PathBuf::from(&out_path);
+    let stem = out_path.file_stem().and_then(|s| s.to_str()).unwrap_or("project");
+    let assets_dir_name = format!("{}_assets", stem);
+    let bundle_assets_dir = out_path.with_file_name(&assets_dir_name);
+
+    let assets_dir = assets_root(&app, &state).await?;
+
+    let (bundle, files) = build_project_bundle(&state.pool(), &project_id, &assets_dir, &assets_dir_name).await?;
+    copy_bundle_assets(&files, &bundle_assets_dir)?;
+
+    let json = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| CommandError::internal("Failed to serialize project", e))?;
+    write_bundle(&out_path, &json)?;
+
+    log::info!(
+        "Exported project {} to {:?} ({} assets)",
+        project_id,
+        out_path,
+        files.len()
+    );
+    Ok(ProjectExport {
+        path: out_path.to_string_lossy().to_string(),
+        asset_count: files.len(),
+    })
+}
+
+/// Copy each (source, path) asset to `bundle_assets_dir/path`, skipping files already there
+pub(crate) fn copy_bundle_assets(files: &[(PathBuf, String)], bundle_assets_dir: &Path) -> Result<(), CommandError> {
+    for (source, target) in files {
+        let target = bundle_assets_dir.join(target);
+        if target == *source {
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| CommandError::io("Failed to create bundle assets folder", e))?;
+        }
+        std::fs::copy(source, &target)
+            .map_err(|e| CommandError::io(&format!("Failed to copy asset {}", source.display()), e))?;
+    }
+    Ok(())
+}
+
+fn write_bundle(path: &Path, json: &str) -> Result<(), CommandError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| CommandError::io("Failed to create export folder", e))?;
+    }
+    std::fs::write(path, json).map_err(|e| CommandError::io("Failed to write bundle", e))
+}
+
+/// Write the bundle's contents into the database under fresh ids, returning the new project id
+pub(crate) async fn import_bundle(
+    conn: &mut sqlx::SqliteConnection,
+    bundle: ProjectBundle,
+    bundle_dir: &Path,
+    assets_dir: &Path,
+    written: &mut Vec<PathBuf>,
+) -> Result<String, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let new_id = || uuid::Uuid::new_v4().to_string();
+
+    // Asset references become library paths; board and card ids are swapped wherever
+    // they appear quoted (e.g. shapes pointing at a card)
+    let mut replacements = Vec::new();
+    for asset in &bundle.assets {
+        let bundled = format!("{}/{}", bundle.assets_dir, asset.path);
+        let source = resolve_asset_path(bundle_dir, &bundled)?;
+        let relative_path = import_asset_copy(&mut *conn, assets_dir, &source, &asset.file_type, written).await?;
+        replacements.push((bundled, relative_path));
+    }
+    replacements.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+
+    let board_ids: HashMap<String, String> =
+        bundle.boards.iter().map(|b| (b.board.id.clone(), new_id())).collect();
+    let card_ids: HashMap<String, String> =
+        bundle.cards.iter().map(|c| (c.id.clone(), new_id())).collect();
+    let rewrite = |text: &str| {
+        let mut text = rewrite_paths(text, &replacements);
+        for (old, new) in board_ids.iter().chain(card_ids.iter()) {
+            text = text.replace(&format!("\"{}\"", old), &format!("\"{}\"", new));
+        }
+        text
+    };
+
+    let project = bundle.project;
+    let project_id = new_id();
+    sqlx::query(&format!(
+        "INSERT INTO projects ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        PROJECT_COLUMNS
+    ))
+    .bind(&project_id)
+    .bind(&project.title)
+    .bind(&project.description)
+    .bind(project.thumbnail_path.as_deref().map(&rewrite))
+    .bind(&project.color)
+    .bind(now)
+    .bind(now)
+    .bind(project.settings.as_deref().map(&rewrite))
+    .bind(project.is_pinned)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| CommandError::db("Failed to import project", e))?;
+
+    // Everything imported is indexed in one batch once the rows are in
+    let mut index = vec![IndexEntity {
+        entity_type: "project".to_string(),
+        entity_id: project_id.clone(),
+        title: project.title.clone(),
+        content: project.description.clone().unwrap_or_default(),
+        tags: String::new(),
+    }];
+
+    for BundleBoard { board, snapshot } in &bundle.boards {
+        let snapshot = snapshot
+            .as_deref()
+            .map(|s| compress_snapshot(&rewrite(s)))
+            .transpose()?;
+        let parent_board_id = board
+            .parent_board_id
+            .as_ref()
+            .map(|id| board_ids.get(id).cloned().unwrap_or_else(|| id.clone()));
+        sqlx::query(
+            "INSERT INTO boards (id, project_id, parent_board_id, title, position, tldraw_snapshot,
+                 snapshot_updated_at, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&board_ids[&board.id])
+        .bind(&project_id)
+        .bind(parent_board_id)
+        .bind(&board.title)
+        .bind(board.position)
+        .bind(snapshot)
+        .bind(board.snapshot_updated_at)
+        .bind(board.created_at)
+        .bind(board.updated_at)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to import board", e))?;
+        index.push(IndexEntity {
+            entity_type: "board".to_string(),
+            entity_id: board_ids[&board.id].clone(),
+            title: board.title.clone(),
+            content: String::new(),
+            tags: String::new(),
+        });
+    }
+
+    for card in &bundle.cards {
+        let content = rewrite(&card.content);
+        let plaintext = card_plaintext(&card.content_type, &content);
+        sqlx::query(&format!(
+            "INSERT INTO cards ({}, preview) VALUES (?, ?, ?, ?, ?, ?, ?, NULL, ?, ?, ?, ?, ?)",
+            CARD_COLUMNS
+        ))
+        .bind(&card_ids[&card.id])
+        .bind(&card.title)
+        .bind(&content)
+        .bind(&card.content_type)
+        .bind(&card.color)
+        .bind(card.is_hidden)
+        .bind(card.word_count)
+        .bind(card.created_at)
+        .bind(card.updated_at)
+        .bind(card.metadata.as_deref().map(&rewrite))
+        .bind(card.is_pinned)
+        .bind(card_preview(&plaintext))
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to import card", e))?;
+        refresh_asset_refs(&mut *conn, &card_ids[&card.id]).await?;
+        index.push(IndexEntity {
+            entity_type: "card".to_string(),
+            entity_id: card_ids[&card.id].clone(),
+            title: card.title.clone().unwrap_or_default(),
+            content: plaintext,
+            tags: String::new(),
+        });
+    }
+
+    search::index_entities(&mut *conn, &index)
+        .await
+        .map_err(|e| CommandError::db("Failed to index imported project", e))?;
+
+    for placement in &bundle.board_cards {
+        let (Some(board_id), Some(card_id)) =
+            (board_ids.get(&placement.board_id), card_ids.get(&placement.card_id))
+        else {
+            return Err(CommandError::Validation(format!(
+                "Bundle places unknown card {} on board {}",
+                placement.card_id, placement.board_id
+            )));
+        };
+        sqlx::query(
+            "INSERT INTO board_cards (board_id, card_id, x, y, width, height, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(board_id)
+        .bind(card_id)
+        .bind(placement.x)
+        .bind(placement.y)
+        .bind(placement.width)
+        .bind(placement.height)
+        .bind(now)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to import board card", e))?;
+    }
+
+    // Tag names are unique, so a tag that already exists by name is reused
+    let mut tag_ids = HashMap::new();
+    for tag in &bundle.tags {
+        let existing: Option<String> = sqlx::query_scalar("SELECT id FROM tags WHERE name = ?")
+            .bind(&tag.name)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| CommandError::db("Failed to look up tag", e))?;
+        let tag_id = match existing {
+            Some(id) => id,
+            None => {
+                let id = new_id();
+                sqlx::query(&format!("INSERT INTO tags ({}) VALUES (?, ?, ?, NULL, ?, ?)", TAG_COLUMNS))
+                    .bind(&id)
+                    .bind(&tag.name)
+                    .bind(&tag.color)
+                    .bind(tag.position)
+                    .bind(now)
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| CommandError::db("Failed to import tag", e))?;
+                id
+            }
+        };
+        tag_ids.insert(tag.id.clone(), tag_id);
+    }
+
+    for card_tag in &bundle.card_tags {
+        let (Some(card_id), Some(tag_id)) = (card_ids.get(&card_tag.card_id), tag_ids.get(&card_tag.tag_id)) else {
+            return Err(CommandError::Validation(format!(
+                "Bundle tags unknown card {} with {}",
+                card_tag.card_id, card_tag.tag_id
+            )));
+        };
+        sqlx::query("INSERT OR IGNORE INTO card_tags (card_id, tag_id, created_at) VALUES (?, ?, ?)")
+            .bind(card_id)
+            .bind(tag_id)
+            .bind(now)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| CommandError::db("Failed to import card tag", e))?;
+    }
+    // Tagging touched the cards; imported cards keep the bundle's timestamps
+    for card in &bundle.cards {
+        keep_card_updated_at(&mut *conn, &card_ids[&card.id], card.updated_at).await?;
+    }
+
+    Ok(project_id)
+}
+
+/// Import a bundle written by `export_project` as a new project and return its id
+/// Everything gets a fresh id and bundled assets are copied into the assets folder.
+/// The import runs in one transaction; on failure nothing is kept, including copied files
+#[command]
+pub async fn import_project(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    bundle_path: String,
+) -> Result<String, CommandError> {
+    let bundle_file = PathBuf::from(&bundle_path);
+    let json = std::fs::read_to_string(&bundle_file)
+        .map_err(|e| CommandError::io("Failed to read bundle", e))?;
+    let bundle: ProjectBundle = serde_json::from_str(&json)
+        .map_err(|e| CommandError::Validation(format!("Invalid project bundle: {}", e)))?;
+    if bundle.schema_version > BUNDLE_SCHEMA_VERSION {
+        return Err(CommandError::Validation(format!(
+            "Bundle format version {} is newer than this app supports ({})",
+            bundle.schema_version, BUNDLE_SCHEMA_VERSION
+        )));
+    }
+
+    let bundle_dir = bundle_file
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let assets_dir = assets_root(&app, &state).await?;
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let mut written = Vec::new();
+    let result = import_bundle(&mut tx, bundle, &bundle_dir, &assets_dir, &mut written).await;
+    let result = match result {
+        Ok(project_id) => tx
+            .commit()
+            .await
+            .map(|_| project_id)
+            .map_err(|e| CommandError::db("Failed to commit transaction", e)),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(project_id) => {
+            log::info!("Imported project {} from {}", project_id, bundle_path);
+            Ok(project_id)
+        }
+        Err(e) => {
+            for path in &written {
+                if let Err(err) = std::fs::remove_file(path) {
+                    log::warn!("Failed to remove imported asset {:?}: {}", path, err);
+                }
+            }
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tauri::Manager;
+
+    use super::*;
+    use crate::commands::tags::{add_tag_to_card, create_tag};
+    use crate::test_support::{seed_card, test_app};
+
+    #[tokio::test]
+    async fn imported_tagged_cards_keep_their_timestamps() {
+        let (app, dir) = test_app().await;
+        seed_card(&app, "c").await;
+        create_tag(app.state(), "t".into(), "todo".into(), None, None, None).await.unwrap();
+        add_tag_to_card(app.state(), "c".into(), "t".into()).await.unwrap();
+        let state = app.state::<AppState>();
+        sqlx::query("UPDATE cards SET updated_at = 1000 WHERE id = 'c'")
+            .execute(&state.writer())
+            .await
+            .unwrap();
+
+        let assets_dir = dir.path().join("assets");
+        let (bundle, _) = build_project_bundle(&state.pool(), "p", &assets_dir, "assets").await.unwrap();
+        let mut tx = state.writer().begin().await.unwrap();
+        let project_id = import_bundle(&mut tx, bundle, dir.path(), &assets_dir, &mut Vec::new())
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let (updated_at, tags): (i64, i64) = sqlx::query_as(
+            "SELECT c.updated_at, (SELECT COUNT(*) FROM card_tags WHERE card_id = c.id)
+             FROM cards c JOIN board_cards bc ON bc.card_id = c.id JOIN boards b ON b.id = bc.board_id
+             WHERE b.project_id = ?",
+        )
+        .bind(&project_id)
+        .fetch_one(&state.pool())
+        .await
+        .unwrap();
+        assert_eq!(tags, 1);
+        assert_eq!(updated_at, 1000);
+    }
+}
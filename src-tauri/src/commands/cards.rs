@@ -0,0 +1,1296 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use tauri::{command, State};
+
+use super::content::{card_plaintext, card_preview};
+use super::database::refresh_asset_refs;
+use super::search;
+use super::sync;
+use crate::db::AppState;
+use crate::error::CommandError;
+use crate::validate;
+
+// ============================================
+// Card Commands
+// ============================================
+
+pub(crate) const CARD_COLUMNS: &str = "id, title, content, content_type, color, is_hidden, \
+     word_count, deleted_at, created_at, updated_at, metadata, is_pinned";
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Card {
+    pub id: String,
+    pub title: Option<String>,
+    pub content: String,
+    pub content_type: String,
+    pub color: Option<String>,
+    pub is_hidden: bool,
+    pub word_count: i64,
+    pub deleted_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub metadata: Option<String>, // JSON
+    #[serde(default)] // missing from older bundles
+    pub is_pinned: bool,
+}
+
+fn count_words(plaintext: &str) -> i64 {
+    plaintext.split_whitespace().count() as i64
+}
+
+/// What is derived from a card's content: its plain text, word count and preview
+struct CardText {
+    plaintext: String,
+    word_count: i64,
+    preview: Option<String>,
+}
+
+/// Derive a card's text on a blocking task
+/// Parsing and counting multi-megabyte content would otherwise hold up the async runtime
+async fn measure_card(content_type: &str, content: &str) -> Result<CardText, CommandError> {
+    let content_type = content_type.to_string();
+    let content = content.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let plaintext = card_plaintext(&content_type, &content);
+        CardText {
+            word_count: count_words(&plaintext),
+            preview: card_preview(&plaintext),
+            plaintext,
+        }
+    })
+    .await
+    .map_err(|e| CommandError::internal("Failed to measure card content", e))
+}
+
+/// Fields of a card to be created
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct NewCard {
+    pub id: String,
+    pub title: Option<String>,
+    pub content: String,
+    pub content_type: String,
+    pub color: Option<String>,
+    pub metadata: Option<String>,
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+}
+
+/// Insert a card, place it on a board and index it, on the caller's connection/transaction
+pub(crate) async fn insert_card(
+    conn: &mut SqliteConnection,
+    board_id: &str,
+    new_card: NewCard,
+) -> Result<Card, CommandError> {
+    let text = measure_card(&new_card.content_type, &new_card.content).await?;
+    insert_measured_card(conn, board_id, new_card, &text).await
+}
+
+/// `insert_card` for content already measured with `measure_card`
+async fn insert_measured_card(
+    conn: &mut SqliteConnection,
+    board_id: &str,
+    new_card: NewCard,
+    text: &CardText,
+) -> Result<Card, CommandError> {
+    let card = insert_card_text(&mut *conn, board_id, new_card, text).await?;
+    search::index_entity(
+        conn,
+        "card",
+        &card.id,
+        card.title.as_deref().unwrap_or_default(),
+        &text.plaintext,
+        "",
+    )
+    .await
+    .map_err(|e| CommandError::db("Failed to index card", e))?;
+
+    Ok(card)
+}
+
+/// `insert_card` without the search index entry, for callers that index in bulk
+/// with `search::index_entities`
+pub(crate) async fn insert_card_row(
+    conn: &mut SqliteConnection,
+    board_id: &str,
+    new_card: NewCard,
+) -> Result<Card, CommandError> {
+    let text = measure_card(&new_card.content_type, &new_card.content).await?;
+    insert_card_text(conn, board_id, new_card, &text).await
+}
+
+async fn insert_card_text(
+    conn: &mut SqliteConnection,
+    board_id: &str,
+    new_card: NewCard,
+    text: &CardText,
+) -> Result<Card, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let board_exists = sqlx::query("SELECT 1 FROM boards WHERE id = ?")
+        .bind(board_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to look up board", e))?
+        .is_some();
+    if !board_exists {
+        return Err(CommandError::NotFound(format!("board not found: {}", board_id)));
+    }
+
+    let card = sqlx::query_as::<_, Card>(&format!(
+        "INSERT INTO cards (id, title, content, content_type, color, is_hidden, word_count, created_at, updated_at, metadata, preview)
+         VALUES (?, ?, ?, ?, ?, 0, ?, ?, ?, ?, ?) RETURNING {}",
+        CARD_COLUMNS
+    ))
+    .bind(&new_card.id)
+    .bind(&new_card.title)
+    .bind(&new_card.content)
+    .bind(&new_card.content_type)
+    .bind(&new_card.color)
+    .bind(text.word_count)
+    .bind(now)
+    .bind(now)
+    .bind(&new_card.metadata)
+    .bind(&text.preview)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+            CommandError::Conflict(format!("card already exists: {}", new_card.id))
+        }
+        _ => CommandError::db("Failed to create card", e),
+    })?;
+
+    sqlx::query(
+        "INSERT INTO board_cards (board_id, card_id, x, y, width, height, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(board_id)
+    .bind(&card.id)
+    .bind(new_card.x)
+    .bind(new_card.y)
+    .bind(new_card.width)
+    .bind(new_card.height)
+    .bind(now)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| CommandError::db("Failed to place card on board", e))?;
+
+    refresh_asset_refs(&mut *conn, &card.id).await?;
+    Ok(card)
+}
+
+/// Set a card's `updated_at` back to `updated_at` after its tags were copied
+/// The `card_tags` triggers bump the card on every tag insert, which is right when
+/// tagging but not for cards copied or imported along with their tags
+pub(crate) async fn keep_card_updated_at(
+    conn: &mut SqliteConnection,
+    card_id: &str,
+    updated_at: i64,
+) -> Result<(), CommandError> {
+    // Skipping unchanged rows keeps the `cards_touch` trigger from firing
+    sqlx::query("UPDATE cards SET updated_at = ? WHERE id = ? AND updated_at IS NOT ?")
+        .bind(updated_at)
+        .bind(card_id)
+        .bind(updated_at)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to update card", e))?;
+    Ok(())
+}
+
+/// Create a card and place it on a board
+/// A title, when given, is trimmed and checked by `validate::title`.
+/// `content_type` defaults to "tiptap" and must be one of `CARD_CONTENT_TYPES`.
+/// Content larger than `validate::max_content_bytes` is rejected
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_card(
+    state: State<'_, AppState>,
+    board_id: String,
+    id: String,
+    title: Option<String>,
+    content: String,
+    content_type: Option<String>,
+    color: Option<String>,
+    metadata: Option<String>,
+    x: Option<f64>,
+    y: Option<f64>,
+    width: Option<f64>,
+    height: Option<f64>,
+) -> Result<Card, CommandError> {
+    let title = validate::optional_title("title", title)?;
+    let content_type = validate::content_type(content_type)?;
+    validate::content("content", &content)?;
+
+    // Measured before the transaction so the writer isn't held while it runs
+    let text = measure_card(&content_type, &content).await?;
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let new_card = NewCard { id, title, content, content_type, color, metadata, x, y, width, height };
+    let card = insert_measured_card(&mut tx, &board_id, new_card, &text).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Created card: {} on board {}", card.id, board_id);
+    Ok(card)
+}
+
+/// Create several cards on a board in one transaction and index them together
+/// Each card is checked as in `create_card` before anything is written; if any card
+/// fails (e.g. an id that already exists) none are created. Returns the cards in the
+/// order given
+#[command]
+pub async fn create_cards(
+    state: State<'_, AppState>,
+    board_id: String,
+    cards: Vec<NewCard>,
+) -> Result<Vec<Card>, CommandError> {
+    if cards.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut measured = Vec::with_capacity(cards.len());
+    for (i, card) in cards.into_iter().enumerate() {
+        let in_card = |e: CommandError| CommandError::Validation(format!("{} (card {}: {})", e, i, card.id));
+        let title = validate::optional_title("title", card.title.clone()).map_err(in_card)?;
+        let content_type = validate::content_type(Some(card.content_type.clone())).map_err(in_card)?;
+        validate::content("content", &card.content).map_err(in_card)?;
+
+        let text = measure_card(&content_type, &card.content).await?;
+        measured.push((NewCard { title, content_type, ..card }, text));
+    }
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let mut created = Vec::with_capacity(measured.len());
+    let mut entities = Vec::with_capacity(measured.len());
+    for (new_card, text) in measured {
+        let card = insert_card_text(&mut tx, &board_id, new_card, &text).await?;
+        entities.push(search::IndexEntity {
+            entity_type: "card".to_string(),
+            entity_id: card.id.clone(),
+            title: card.title.clone().unwrap_or_default(),
+            content: text.plaintext,
+            tags: String::new(),
+        });
+        created.push(card);
+    }
+    search::index_entities(&mut tx, &entities)
+        .await
+        .map_err(|e| CommandError::db("Failed to index cards", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Created {} cards on board {}", created.len(), board_id);
+    Ok(created)
+}
+
+/// Get cards, pinned ones first, then most recently updated first
+/// When `board_id` is given only the cards placed on that board are returned.
+/// `content_type` and `color` match exactly, `search` matches anywhere in the title;
+/// all given filters must hold. Trashed cards are left out unless `include_deleted` is set.
+/// `limit`/`offset` page through the results
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_cards(
+    state: State<'_, AppState>,
+    board_id: Option<String>,
+    include_deleted: Option<bool>,
+    content_type: Option<String>,
+    color: Option<String>,
+    search: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<Card>, CommandError> {
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new(format!("SELECT {} FROM cards WHERE 1 = 1", CARD_COLUMNS));
+    if let Some(board_id) = &board_id {
+        query
+            .push(" AND id IN (SELECT card_id FROM board_cards WHERE board_id = ")
+            .push_bind(board_id)
+            .push(")");
+    }
+    if !include_deleted.unwrap_or(false) {
+        query.push(" AND deleted_at IS NULL");
+    }
+    if let Some(content_type) = content_type {
+        query.push(" AND COALESCE(content_type, 'tiptap') = ").push_bind(content_type);
+    }
+    if let Some(color) = color {
+        query.push(" AND color = ").push_bind(color);
+    }
+    if let Some(search) = search.as_deref().map(str::trim).filter(|search| !search.is_empty()) {
+        // % and _ in the search text match literally
+        let pattern = format!(
+            "%{}%",
+            search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        query.push(" AND title LIKE ").push_bind(pattern).push(" ESCAPE '\\'");
+    }
+    // SQLite treats a negative LIMIT as "no limit"
+    query.push(" ORDER BY is_pinned DESC, updated_at DESC LIMIT ").push_bind(limit.unwrap_or(-1));
+    query.push(" OFFSET ").push_bind(offset.unwrap_or(0));
+
+    let cards = query
+        .build_query_as::<Card>()
+        .fetch_all(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to get cards", e))?;
+
+    Ok(cards)
+}
+
+/// Just enough of a card for list views, without its content
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CardPreview {
+    pub id: String,
+    pub title: Option<String>,
+    pub preview: Option<String>,
+    pub updated_at: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct CardPreviewRow {
+    #[sqlx(flatten)]
+    card: CardPreview,
+    content: Option<String>, // only for cards saved before previews were stored
+    content_type: Option<String>,
+}
+
+/// Get id, title and text preview of the cards on a board, most recently updated first
+/// Trashed cards are left out
+#[command]
+pub async fn get_card_previews(state: State<'_, AppState>, board_id: String) -> Result<Vec<CardPreview>, CommandError> {
+    let rows = sqlx::query_as::<_, CardPreviewRow>(
+        "SELECT id, title, preview, updated_at, CASE WHEN preview IS NULL THEN content END AS content, content_type
+         FROM cards
+         WHERE deleted_at IS NULL AND id IN (SELECT card_id FROM board_cards WHERE board_id = ?)
+         ORDER BY updated_at DESC",
+    )
+    .bind(&board_id)
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get card previews", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let mut card = row.card;
+            if card.preview.is_none() {
+                if let Some(content) = row.content {
+                    card.preview = card_preview(&card_plaintext(row.content_type.as_deref().unwrap_or("tiptap"), &content));
+                }
+            }
+            card
+        })
+        .collect())
+}
+
+/// Get cards carrying any of the given tags, or all of them when `match_all` is set
+/// Trashed cards are left out; results are most recently updated first
+#[command]
+pub async fn get_cards_by_tags(
+    state: State<'_, AppState>,
+    tag_ids: Vec<String>,
+    match_all: bool,
+) -> Result<Vec<Card>, CommandError> {
+    let tag_ids: std::collections::BTreeSet<String> = tag_ids.into_iter().collect();
+    if tag_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new(format!(
+        "SELECT {} FROM cards WHERE deleted_at IS NULL AND id IN (SELECT card_id FROM card_tags WHERE tag_id IN (",
+        CARD_COLUMNS
+    ));
+    let mut separated = query.separated(", ");
+    for tag_id in &tag_ids {
+        separated.push_bind(tag_id);
+    }
+    query.push(")");
+    if match_all {
+        query
+            .push(" GROUP BY card_id HAVING COUNT(DISTINCT tag_id) = ")
+            .push_bind(tag_ids.len() as i64);
+    }
+    query.push(") ORDER BY updated_at DESC");
+
+    let cards = query
+        .build_query_as::<Card>()
+        .fetch_all(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to get cards by tags", e))?;
+
+    Ok(cards)
+}
+
+/// `CARD_COLUMNS` qualified with a table alias, for queries that join other tables
+pub(crate) fn card_columns(alias: &str) -> String {
+    CARD_COLUMNS
+        .split(',')
+        .map(|column| format!("{}.{}", alias, column.trim()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Get cards without any tag, most recently updated first
+/// Trashed cards are left out; `limit`/`offset` page through the results
+#[command]
+pub async fn get_untagged_cards(
+    state: State<'_, AppState>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<Card>, CommandError> {
+    // SQLite treats a negative LIMIT as "no limit"
+    let cards = sqlx::query_as::<_, Card>(&format!(
+        "SELECT {} FROM cards c
+         LEFT JOIN card_tags ct ON ct.card_id = c.id
+         WHERE ct.card_id IS NULL AND c.deleted_at IS NULL
+         ORDER BY c.updated_at DESC LIMIT ? OFFSET ?",
+        card_columns("c")
+    ))
+    .bind(limit.unwrap_or(-1))
+    .bind(offset.unwrap_or(0))
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get untagged cards", e))?;
+
+    Ok(cards)
+}
+
+/// Get a single card by id
+#[command]
+pub async fn get_card(state: State<'_, AppState>, id: String) -> Result<Option<Card>, CommandError> {
+    let card = sqlx::query_as::<_, Card>(&format!("SELECT {} FROM cards WHERE id = ?", CARD_COLUMNS))
+        .bind(&id)
+        .fetch_optional(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to get card", e))?;
+
+    Ok(card)
+}
+
+/// Update a card, touching only the fields that were provided
+/// Word count is recomputed whenever content changes, the previous content is kept
+/// as a revision, and the search index is refreshed when the title or content changes.
+/// A new title is trimmed and checked by `validate::title`; content larger than
+/// `validate::max_content_bytes` is rejected.
+/// Returns false if no card with this id exists
+#[command]
+pub async fn update_card(
+    state: State<'_, AppState>,
+    id: String,
+    title: Option<String>,
+    content: Option<String>,
+    color: Option<String>,
+    is_hidden: Option<bool>,
+    metadata: Option<String>,
+) -> Result<bool, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let reindex = title.is_some() || content.is_some();
+    let title = validate::optional_title("title", title)?;
+    if let Some(content) = &content {
+        validate::content("content", content)?;
+    }
+
+    // New content is measured as the card's own content type, before the
+    // transaction so the writer isn't held while it runs
+    let text = match &content {
+        Some(content) => {
+            let content_type: Option<String> = sqlx::query_scalar("SELECT content_type FROM cards WHERE id = ?")
+                .bind(&id)
+                .fetch_optional(&state.pool())
+                .await
+                .map_err(|e| CommandError::db("Failed to get card", e))?
+                .flatten();
+            Some(measure_card(content_type.as_deref().unwrap_or("tiptap"), content).await?)
+        }
+        None => None,
+    };
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    if let Some(content) = &content {
+        save_revision(&mut tx, &id, Some(content), now).await?;
+    }
+
+    let refs_changed = content.is_some() || metadata.is_some();
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new("UPDATE cards SET updated_at = ");
+    query.push_bind(now);
+    if let Some(title) = title {
+        query.push(", title = ").push_bind(title);
+    }
+    if let (Some(content), Some(text)) = (content, &text) {
+        query.push(", word_count = ").push_bind(text.word_count);
+        query.push(", preview = ").push_bind(text.preview.clone());
+        query.push(", content = ").push_bind(content);
+    }
+    if let Some(color) = color {
+        query.push(", color = ").push_bind(color);
+    }
+    if let Some(is_hidden) = is_hidden {
+        query.push(", is_hidden = ").push_bind(is_hidden);
+    }
+    if let Some(metadata) = metadata {
+        query.push(", metadata = ").push_bind(metadata);
+    }
+    query.push(" WHERE id = ").push_bind(&id);
+    query.push(format!(" RETURNING {}", CARD_COLUMNS));
+
+    let card = match query
+        .build_query_as::<Card>()
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to update card", e))?
+    {
+        Some(card) => card,
+        None => {
+            log::warn!("Card not found: {}", id);
+            return Ok(false);
+        }
+    };
+
+    // A trashed card stays out of the index until it is restored
+    if reindex && card.deleted_at.is_none() {
+        // A title-only edit keeps the indexed text rather than measuring the content
+        // again while the writer is held; only a card missing from the index is measured
+        let plaintext = match text {
+            Some(text) => text.plaintext,
+            None => match search::indexed_content(&mut tx, "card", &card.id)
+                .await
+                .map_err(|e| CommandError::db("Failed to get indexed card", e))?
+            {
+                Some(indexed) => indexed,
+                None => measure_card(&card.content_type, &card.content).await?.plaintext,
+            },
+        };
+        search::index_entity(
+            &mut tx,
+            "card",
+            &card.id,
+            card.title.as_deref().unwrap_or_default(),
+            &plaintext,
+            "",
+        )
+        .await
+        .map_err(|e| CommandError::db("Failed to index card", e))?;
+    }
+    if refs_changed {
+        refresh_asset_refs(&mut tx, &card.id).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Updated card: {}", id);
+    Ok(true)
+}
+
+/// Move a card from one board to another
+/// The canvas geometry is kept except for the parts given. Both boards must exist.
+/// Returns false, changing nothing, if the card isn't on `from_board`; if it is
+/// already on `to_board` too, the two placements merge
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn move_card(
+    state: State<'_, AppState>,
+    card_id: String,
+    from_board: String,
+    to_board: String,
+    x: Option<f64>,
+    y: Option<f64>,
+    width: Option<f64>,
+    height: Option<f64>,
+) -> Result<bool, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    for board_id in [&from_board, &to_board] {
+        let board_exists = sqlx::query("SELECT 1 FROM boards WHERE id = ?")
+            .bind(board_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to look up board", e))?
+            .is_some();
+        if !board_exists {
+            return Err(CommandError::NotFound(format!("board not found: {}", board_id)));
+        }
+    }
+
+    let moved = sqlx::query(
+        "UPDATE OR REPLACE board_cards
+         SET board_id = ?, x = COALESCE(?, x), y = COALESCE(?, y),
+             width = COALESCE(?, width), height = COALESCE(?, height)
+         WHERE board_id = ? AND card_id = ?",
+    )
+    .bind(&to_board)
+    .bind(x)
+    .bind(y)
+    .bind(width)
+    .bind(height)
+    .bind(&from_board)
+    .bind(&card_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to move card", e))?
+    .rows_affected()
+        > 0;
+    if !moved {
+        return Ok(false);
+    }
+
+    sqlx::query("UPDATE cards SET updated_at = ? WHERE id = ?")
+        .bind(now)
+        .bind(&card_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to move card", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Moved card {} from board {} to {}", card_id, from_board, to_board);
+    Ok(true)
+}
+
+/// Set where a card sits on a board's canvas
+/// Returns false if the card isn't placed on that board
+#[command]
+pub async fn update_card_position(
+    state: State<'_, AppState>,
+    card_id: String,
+    board_id: String,
+    x: f64,
+    y: f64,
+) -> Result<bool, CommandError> {
+    let updated = sqlx::query("UPDATE board_cards SET x = ?, y = ? WHERE board_id = ? AND card_id = ?")
+        .bind(x)
+        .bind(y)
+        .bind(&board_id)
+        .bind(&card_id)
+        .execute(&state.writer())
+        .await
+        .map_err(|e| CommandError::db("Failed to update card position", e))?
+        .rows_affected()
+        > 0;
+
+    Ok(updated)
+}
+
+/// Pin a card to the top of `get_cards`, or unpin it
+/// Returns false if no card with this id exists
+#[command]
+pub async fn set_card_pinned(state: State<'_, AppState>, id: String, pinned: bool) -> Result<bool, CommandError> {
+    let updated = sqlx::query("UPDATE cards SET is_pinned = ?, updated_at = ? WHERE id = ?")
+        .bind(pinned)
+        .bind(chrono::Utc::now().timestamp_millis())
+        .bind(&id)
+        .execute(&state.writer())
+        .await
+        .map_err(|e| CommandError::db("Failed to pin card", e))?
+        .rows_affected()
+        > 0;
+
+    if updated {
+        log::info!("{} card: {}", if pinned { "Pinned" } else { "Unpinned" }, id);
+    } else {
+        log::warn!("Card not found: {}", id);
+    }
+    Ok(updated)
+}
+
+/// A card together with its geometry on one board
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionedCard {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub card: Card,
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+}
+
+/// Get the cards on a board along with their canvas geometry
+/// Trashed cards are left out; results are ordered top to bottom, then left to right
+#[command]
+pub async fn get_cards_with_positions(
+    state: State<'_, AppState>,
+    board_id: String,
+) -> Result<Vec<PositionedCard>, CommandError> {
+    // The placement is a subquery so its created_at doesn't clash with the card's
+    let cards = sqlx::query_as::<_, PositionedCard>(&format!(
+        "SELECT {}, x, y, width, height FROM cards
+         JOIN (SELECT card_id, x, y, width, height FROM board_cards WHERE board_id = ?)
+             ON card_id = id
+         WHERE deleted_at IS NULL
+         ORDER BY y, x",
+        CARD_COLUMNS
+    ))
+    .bind(&board_id)
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get cards", e))?;
+
+    Ok(cards)
+}
+
+// ============================================
+// Trash Commands
+// ============================================
+
+/// Move a card to the trash and drop it from the search index
+/// Returns false if the card doesn't exist or is already trashed
+#[command]
+pub async fn delete_card(state: State<'_, AppState>, id: String) -> Result<bool, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let trashed = sqlx::query(
+        "UPDATE cards SET deleted_at = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL",
+    )
+    .bind(now)
+    .bind(now)
+    .bind(&id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to delete card", e))?
+    .rows_affected()
+        > 0;
+
+    if trashed {
+        search::remove_entity(&mut tx, "card", &id)
+            .await
+            .map_err(|e| CommandError::db("Failed to remove card from index", e))?;
+        sync::record_tombstone(&mut tx, "card", &id, now).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    if trashed {
+        log::info!("Moved card to trash: {}", id);
+    }
+    Ok(trashed)
+}
+
+/// Move several cards to the trash in one transaction and drop them from the search index
+/// Unknown or already trashed ids are skipped. Returns the number of cards trashed
+#[command]
+pub async fn bulk_delete_cards(state: State<'_, AppState>, ids: Vec<String>) -> Result<u64, CommandError> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new("UPDATE cards SET deleted_at = ");
+    query.push_bind(now).push(", updated_at = ").push_bind(now);
+    query.push(" WHERE deleted_at IS NULL AND id IN (");
+    let mut separated = query.separated(", ");
+    for id in &ids {
+        separated.push_bind(id);
+    }
+    query.push(") RETURNING id");
+
+    let trashed: Vec<String> = query
+        .build_query_scalar()
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to delete cards", e))?;
+
+    if !trashed.is_empty() {
+        let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new(
+            "DELETE FROM search_index WHERE entity_type = 'card' AND entity_id IN (",
+        );
+        let mut separated = query.separated(", ");
+        for id in &trashed {
+            separated.push_bind(id);
+        }
+        query.push(")");
+        query
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to remove cards from index", e))?;
+    }
+    for id in &trashed {
+        sync::record_tombstone(&mut tx, "card", id, now).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Moved {} cards to trash", trashed.len());
+    Ok(trashed.len() as u64)
+}
+
+/// Restore a trashed card and add it back to the search index
+/// Returns false if the card doesn't exist or isn't in the trash
+#[command]
+pub async fn restore_card(state: State<'_, AppState>, id: String) -> Result<bool, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let card = sqlx::query_as::<_, Card>(&format!(
+        "UPDATE cards SET deleted_at = NULL, updated_at = ?
+         WHERE id = ? AND deleted_at IS NOT NULL
+         RETURNING {}",
+        CARD_COLUMNS
+    ))
+    .bind(now)
+    .bind(&id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to restore card", e))?;
+
+    let Some(card) = card else {
+        return Ok(false);
+    };
+    sync::clear_tombstone(&mut tx, "card", &card.id).await?;
+
+    search::index_entity(
+        &mut tx,
+        "card",
+        &card.id,
+        card.title.as_deref().unwrap_or_default(),
+        &card_plaintext(&card.content_type, &card.content),
+        "",
+    )
+    .await
+    .map_err(|e| CommandError::db("Failed to index card", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Restored card from trash: {}", id);
+    Ok(true)
+}
+
+/// Permanently delete trashed cards
+/// With `older_than_ms` only cards trashed at least that long ago are purged,
+/// otherwise the whole trash is emptied. Returns the number of purged cards
+#[command]
+pub async fn purge_deleted_cards(
+    state: State<'_, AppState>,
+    older_than_ms: Option<i64>,
+) -> Result<u64, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let cutoff = now - older_than_ms.unwrap_or(0);
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    sqlx::query(
+        "INSERT OR REPLACE INTO tombstones (entity_type, entity_id, deleted_at)
+         SELECT 'card', id, ? FROM cards WHERE deleted_at IS NOT NULL AND deleted_at <= ?",
+    )
+    .bind(now)
+    .bind(cutoff)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to record deletion", e))?;
+
+    let purged = sqlx::query("DELETE FROM cards WHERE deleted_at IS NOT NULL AND deleted_at <= ?")
+        .bind(cutoff)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to purge deleted cards", e))?
+        .rows_affected();
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Purged {} deleted cards", purged);
+    Ok(purged)
+}
+
+// ============================================
+// Card Revisions
+// ============================================
+
+/// Revisions kept per card; older ones are pruned as new ones are saved
+const MAX_REVISIONS_PER_CARD: i64 = 50;
+
+/// Content a card had before an edit
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CardRevision {
+    pub id: String,
+    pub card_id: String,
+    pub content: Option<String>,
+    pub word_count: i64,
+    pub saved_at: i64,
+}
+
+/// Keep a card's current content as a revision and prune the oldest beyond the cap
+/// Does nothing for cards that don't exist or whose content is already `new_content`
+async fn save_revision(
+    conn: &mut SqliteConnection,
+    card_id: &str,
+    new_content: Option<&str>,
+    now: i64,
+) -> Result<(), CommandError> {
+    sqlx::query(
+        "INSERT INTO card_revisions (id, card_id, content, word_count, saved_at)
+         SELECT ?, id, content, COALESCE(word_count, 0), ? FROM cards WHERE id = ? AND content IS NOT ?",
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(now)
+    .bind(card_id)
+    .bind(new_content)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| CommandError::db("Failed to save card revision", e))?;
+
+    sqlx::query(
+        "DELETE FROM card_revisions WHERE card_id = ?1 AND id NOT IN (
+             SELECT id FROM card_revisions WHERE card_id = ?1
+             ORDER BY saved_at DESC, rowid DESC LIMIT ?2
+         )",
+    )
+    .bind(card_id)
+    .bind(MAX_REVISIONS_PER_CARD)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| CommandError::db("Failed to prune card revisions", e))?;
+
+    Ok(())
+}
+
+/// Get the saved revisions of a card, newest first
+#[command]
+pub async fn get_card_revisions(
+    state: State<'_, AppState>,
+    card_id: String,
+    limit: Option<i64>,
+) -> Result<Vec<CardRevision>, CommandError> {
+    // SQLite treats a negative LIMIT as "no limit"
+    let revisions = sqlx::query_as::<_, CardRevision>(
+        "SELECT id, card_id, content, word_count, saved_at FROM card_revisions
+         WHERE card_id = ?
+         ORDER BY saved_at DESC, rowid DESC LIMIT ?",
+    )
+    .bind(&card_id)
+    .bind(limit.unwrap_or(-1))
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get card revisions", e))?;
+
+    Ok(revisions)
+}
+
+/// Put a revision's content back on its card
+/// The content being replaced is saved as a revision first, so a restore can be undone
+#[command]
+pub async fn restore_card_revision(state: State<'_, AppState>, revision_id: String) -> Result<Card, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let revision = sqlx::query_as::<_, CardRevision>(
+        "SELECT id, card_id, content, word_count, saved_at FROM card_revisions WHERE id = ?",
+    )
+    .bind(&revision_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to get card revision", e))?
+    .ok_or_else(|| CommandError::NotFound(format!("revision not found: {}", revision_id)))?;
+
+    save_revision(&mut tx, &revision.card_id, revision.content.as_deref(), now).await?;
+
+    let content_type: Option<String> = sqlx::query_scalar("SELECT content_type FROM cards WHERE id = ?")
+        .bind(&revision.card_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to get card", e))?
+        .flatten();
+    let plaintext = card_plaintext(
+        content_type.as_deref().unwrap_or("tiptap"),
+        revision.content.as_deref().unwrap_or_default(),
+    );
+
+    // One statement, so the returned card has the `updated_at` that was stored
+    let card = sqlx::query_as::<_, Card>(&format!(
+        "UPDATE cards SET content = ?, word_count = ?, preview = ?, updated_at = ? WHERE id = ? RETURNING {}",
+        CARD_COLUMNS
+    ))
+    .bind(&revision.content)
+    .bind(revision.word_count)
+    .bind(card_preview(&plaintext))
+    .bind(now)
+    .bind(&revision.card_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to restore card revision", e))?;
+
+    if card.deleted_at.is_none() {
+        search::index_entity(
+            &mut tx,
+            "card",
+            &card.id,
+            card.title.as_deref().unwrap_or_default(),
+            &plaintext,
+            "",
+        )
+        .await
+        .map_err(|e| CommandError::db("Failed to index card", e))?;
+    }
+    refresh_asset_refs(&mut tx, &card.id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Restored revision {} of card {}", revision_id, card.id);
+    Ok(card)
+}
+
+// ============================================
+// Duplicate Detection
+// ============================================
+
+/// Cards whose text is the same once case and whitespace are ignored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub hash: String,
+    pub card_ids: Vec<String>,
+}
+
+/// SHA-256 of a card's plain text, lowercased and with whitespace runs collapsed
+/// None for cards without any text, which would otherwise all match each other
+fn normalized_content_hash(content_type: &str, content: &str) -> Option<String> {
+    use sha2::{Digest, Sha256};
+
+    let plaintext = card_plaintext(content_type, content).to_lowercase();
+    let normalized = plaintext.split_whitespace().collect::<Vec<_>>().join(" ");
+    if normalized.is_empty() {
+        return None;
+    }
+    Some(format!("{:x}", Sha256::digest(normalized.as_bytes())))
+}
+
+/// Find groups of cards with the same normalized text (see `normalized_content_hash`)
+/// Tiptap cards are compared by `tiptap_to_plaintext`, markdown and code cards as
+/// written. Trashed and empty cards are left out. Largest groups come first, and
+/// each group lists its oldest card first
+#[command]
+pub async fn find_duplicate_cards(state: State<'_, AppState>) -> Result<Vec<DuplicateGroup>, CommandError> {
+    // Cards are read in pages so large libraries aren't loaded all at once
+    const CARD_BATCH_SIZE: i64 = 500;
+
+    let mut groups: std::collections::HashMap<String, Vec<(i64, String)>> = std::collections::HashMap::new();
+    let mut last_id = String::new();
+    loop {
+        let cards: Vec<(String, String, String, i64)> = sqlx::query_as(
+            "SELECT id, COALESCE(content, ''), COALESCE(content_type, 'tiptap'), created_at FROM cards
+             WHERE id > ? AND deleted_at IS NULL
+             ORDER BY id LIMIT ?",
+        )
+        .bind(&last_id)
+        .bind(CARD_BATCH_SIZE)
+        .fetch_all(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to read cards", e))?;
+
+        let Some((id, _, _, _)) = cards.last() else { break };
+        last_id = id.clone();
+
+        // Converting and hashing content is CPU bound, keep it off the async runtime
+        let hashed = tauri::async_runtime::spawn_blocking(move || {
+            cards
+                .into_iter()
+                .filter_map(|(id, content, content_type, created_at)| {
+                    normalized_content_hash(&content_type, &content).map(|hash| (hash, created_at, id))
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .map_err(|e| CommandError::internal("Failed to hash cards", e))?;
+        for (hash, created_at, id) in hashed {
+            groups.entry(hash).or_default().push((created_at, id));
+        }
+    }
+
+    let mut duplicates: Vec<DuplicateGroup> = groups
+        .into_iter()
+        .filter(|(_, cards)| cards.len() > 1)
+        .map(|(hash, mut cards)| {
+            cards.sort();
+            DuplicateGroup { hash, card_ids: cards.into_iter().map(|(_, id)| id).collect() }
+        })
+        .collect();
+    duplicates.sort_by(|a, b| b.card_ids.len().cmp(&a.card_ids.len()).then_with(|| a.card_ids[0].cmp(&b.card_ids[0])));
+
+    log::info!("Found {} groups of duplicate cards", duplicates.len());
+    Ok(duplicates)
+}
+
+#[cfg(test)]
+mod tests {
+    use tauri::Manager;
+
+    use super::*;
+    use crate::commands::content::CARD_PREVIEW_CHARS;
+    use crate::test_support::{seed_card, test_app};
+
+    async fn indexed(state: &AppState, id: &str) -> bool {
+        sqlx::query("SELECT 1 FROM search_index WHERE entity_type = 'card' AND entity_id = ?")
+            .bind(id)
+            .fetch_optional(&state.pool())
+            .await
+            .unwrap()
+            .is_some()
+    }
+
+    #[tokio::test]
+    async fn update_card_keeps_trashed_cards_out_of_the_index() {
+        let (app, _dir) = test_app().await;
+        seed_card(&app, "c").await;
+        assert!(indexed(&app.state::<AppState>(), "c").await);
+
+        assert!(delete_card(app.state(), "c".into()).await.unwrap());
+        assert!(!indexed(&app.state::<AppState>(), "c").await);
+
+        let content = "{\"type\":\"doc\"}".to_string();
+        assert!(update_card(app.state(), "c".into(), Some("Renamed".into()), Some(content), None, None, None)
+            .await
+            .unwrap());
+        assert!(!indexed(&app.state::<AppState>(), "c").await);
+    }
+
+    #[tokio::test]
+    async fn update_card_trims_and_validates_the_title() {
+        let (app, _dir) = test_app().await;
+        seed_card(&app, "c").await;
+
+        let bad = update_card(app.state(), "c".into(), Some("bad\0title".into()), None, None, None, None).await;
+        assert!(matches!(bad, Err(CommandError::Validation(_))));
+
+        assert!(update_card(app.state(), "c".into(), Some("  Renamed  ".into()), None, None, None, None)
+            .await
+            .unwrap());
+        let title: Option<String> = sqlx::query_scalar("SELECT title FROM cards WHERE id = 'c'")
+            .fetch_one(&app.state::<AppState>().pool())
+            .await
+            .unwrap();
+        assert_eq!(title.as_deref(), Some("Renamed"));
+    }
+
+    #[tokio::test]
+    async fn title_only_edit_keeps_the_indexed_text() {
+        let (app, _dir) = test_app().await;
+        seed_card(&app, "c").await;
+        let content = "{\"type\":\"doc\",\"content\":[{\"type\":\"paragraph\",\"content\":[{\"type\":\"text\",\"text\":\"alpha beta\"}]}]}";
+        update_card(app.state(), "c".into(), None, Some(content.into()), None, None, None)
+            .await
+            .unwrap();
+
+        update_card(app.state(), "c".into(), Some("Renamed".into()), None, None, None, None)
+            .await
+            .unwrap();
+        let row: (String, String) = sqlx::query_as(
+            "SELECT title, content FROM search_index WHERE entity_type = 'card' AND entity_id = 'c'"
+        )
+            .fetch_one(&app.state::<AppState>().pool())
+            .await
+            .unwrap();
+        assert_eq!(row, ("Renamed".to_string(), "alpha beta".to_string()));
+    }
+
+    #[tokio::test]
+    async fn content_over_the_limit_is_rejected() {
+        let (app, _dir) = test_app().await;
+        seed_card(&app, "c").await;
+        let too_large = "a".repeat(validate::max_content_bytes() + 1);
+
+        let created = create_card(
+            app.state(),
+            "b".into(),
+            "big".into(),
+            None,
+            too_large.clone(),
+            Some("markdown".into()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+        assert!(matches!(created, Err(CommandError::Validation(_))));
+
+        let updated = update_card(app.state(), "c".into(), None, Some(too_large), None, None, None).await;
+        assert!(matches!(updated, Err(CommandError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn multi_megabyte_content_is_measured_off_the_runtime() {
+        // 4 MiB of five-byte words
+        let words = 4 * 1024 * 1024 / 5;
+        let content = "word ".repeat(words);
+
+        let text = measure_card("markdown", &content).await.unwrap();
+        assert_eq!(text.word_count, words as i64);
+        assert_eq!(text.plaintext.len(), content.len());
+        let preview = text.preview.unwrap();
+        assert!(preview.starts_with("word word"));
+        assert!(preview.chars().count() <= CARD_PREVIEW_CHARS + 1);
+
+        let doc = serde_json::json!({
+            "type": "doc",
+            "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": content }] }],
+        })
+        .to_string();
+        let text = measure_card("tiptap", &doc).await.unwrap();
+        assert_eq!(text.word_count, words as i64);
+    }
+}
@@ -0,0 +1,892 @@
+use serde_json::Value;
+
+// ============================================
+// Card Content Helpers
+// ============================================
+
+/// Content types a card can be created with
+pub const CARD_CONTENT_TYPES: &[&str] = &["tiptap", "markdown", "code"];
+
+/// Plain text of a card for indexing, word counts and previews
+/// Markdown and code cards are already text; anything else goes through `tiptap_to_plaintext`
+pub fn card_plaintext(content_type: &str, content: &str) -> String {
+    match content_type {
+        "markdown" | "code" => content.to_string(),
+        _ => tiptap_to_plaintext(content),
+    }
+}
+
+/// Characters of card text kept in a stored preview
+pub const CARD_PREVIEW_CHARS: usize = 140;
+
+/// First `CARD_PREVIEW_CHARS` characters of a card's plain text, on one line
+/// None when the card has no text
+pub fn card_preview(plaintext: &str) -> Option<String> {
+    let text = plaintext.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    match text.char_indices().nth(CARD_PREVIEW_CHARS) {
+        Some((end, _)) => Some(format!("{}…", text[..end].trim_end())),
+        None => Some(text),
+    }
+}
+
+/// Convert a tiptap JSON document to plain text for indexing and previews
+/// Blocks (paragraphs, headings, list items...) go on their own line and list
+/// items get a bullet, number or checkbox marker. Content that isn't a tiptap
+/// document is returned unchanged
+pub fn tiptap_to_plaintext(content: &str) -> String {
+    let doc: Value = match serde_json::from_str(content) {
+        Ok(doc @ Value::Object(_)) if doc.get("type").is_some() => doc,
+        _ => return content.to_string(),
+    };
+
+    let mut lines = Vec::new();
+    collect_blocks(&doc, 0, "", &mut lines);
+    lines.join("\n")
+}
+
+fn node_type(node: &Value) -> &str {
+    node.get("type").and_then(Value::as_str).unwrap_or_default()
+}
+
+fn children(node: &Value) -> &[Value] {
+    node.get("content")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// Render a block node as lines; `marker` prefixes the first line of a list item
+fn collect_blocks(node: &Value, depth: usize, marker: &str, lines: &mut Vec<String>) {
+    match node_type(node) {
+        "bulletList" => {
+            for item in children(node) {
+                collect_blocks(item, depth, "• ", lines);
+            }
+        }
+        "orderedList" => {
+            let start = node.pointer("/attrs/start").and_then(Value::as_u64).unwrap_or(1);
+            for (index, item) in children(node).iter().enumerate() {
+                collect_blocks(item, depth, &format!("{}. ", start + index as u64), lines);
+            }
+        }
+        "taskList" => {
+            for item in children(node) {
+                let checked = item.pointer("/attrs/checked").and_then(Value::as_bool).unwrap_or(false);
+                collect_blocks(item, depth, if checked { "[x] " } else { "[ ] " }, lines);
+            }
+        }
+        "listItem" | "taskItem" => {
+            // The first block carries the marker, nested blocks are indented below it
+            for (index, child) in children(node).iter().enumerate() {
+                if index == 0 {
+                    collect_blocks(child, depth, marker, lines);
+                } else {
+                    collect_blocks(child, depth + 1, "", lines);
+                }
+            }
+        }
+        "doc" | "blockquote" | "table" | "tableRow" | "tableCell" | "tableHeader" => {
+            for child in children(node) {
+                collect_blocks(child, depth, marker, lines);
+            }
+        }
+        _ => {
+            let mut text = String::new();
+            collect_inline(node, &mut text);
+            let text = text.trim();
+            if !text.is_empty() {
+                lines.push(format!("{}{}{}", "  ".repeat(depth), marker, text));
+            }
+        }
+    }
+}
+
+fn collect_inline(node: &Value, out: &mut String) {
+    match node_type(node) {
+        "text" => out.push_str(node.get("text").and_then(Value::as_str).unwrap_or_default()),
+        "hardBreak" => out.push('\n'),
+        _ => {
+            for child in children(node) {
+                collect_inline(child, out);
+            }
+        }
+    }
+}
+
+/// Convert a tiptap JSON document to Markdown
+/// `resolve_image` maps each image `src` to the path the Markdown should link to.
+/// Content that isn't a tiptap document is returned unchanged
+pub fn tiptap_to_markdown(content: &str, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    let doc: Value = match serde_json::from_str(content) {
+        Ok(doc @ Value::Object(_)) if doc.get("type").is_some() => doc,
+        _ => return content.to_string(),
+    };
+
+    render_block(&doc, resolve_image)
+}
+
+fn render_block(node: &Value, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    match node_type(node) {
+        "doc" => render_blocks(children(node), "\n\n", resolve_image),
+        "heading" => {
+            let level = node.pointer("/attrs/level").and_then(Value::as_u64).unwrap_or(1).clamp(1, 6);
+            format!("{} {}", "#".repeat(level as usize), render_inline(node, resolve_image))
+        }
+        "bulletList" => children(node)
+            .iter()
+            .map(|item| render_list_item(item, "- ", 2, resolve_image))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "orderedList" => {
+            let start = node.pointer("/attrs/start").and_then(Value::as_u64).unwrap_or(1);
+            children(node)
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    let marker = format!("{}. ", start + index as u64);
+                    let indent = marker.len();
+                    render_list_item(item, &marker, indent, resolve_image)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        "taskList" => children(node)
+            .iter()
+            .map(|item| {
+                let checked = item.pointer("/attrs/checked").and_then(Value::as_bool).unwrap_or(false);
+                render_list_item(item, if checked { "- [x] " } else { "- [ ] " }, 2, resolve_image)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "blockquote" => render_blocks(children(node), "\n\n", resolve_image)
+            .lines()
+            .map(|line| if line.is_empty() { ">".to_string() } else { format!("> {}", line) })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "codeBlock" => {
+            let language = node.pointer("/attrs/language").and_then(Value::as_str).unwrap_or_default();
+            let mut code = String::new();
+            collect_inline(node, &mut code);
+            format!("```{}\n{}\n```", language, code)
+        }
+        "horizontalRule" => "---".to_string(),
+        "image" => render_image(node, resolve_image),
+        "table" => render_table(node, resolve_image),
+        _ => render_inline(node, resolve_image),
+    }
+}
+
+fn render_blocks(nodes: &[Value], separator: &str, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    nodes
+        .iter()
+        .map(|node| render_block(node, resolve_image))
+        .filter(|block| !block.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Render a list item: the marker goes on the first line, the rest is indented under it
+fn render_list_item(item: &Value, marker: &str, indent: usize, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    let body = render_blocks(children(item), "\n", resolve_image);
+    let padding = " ".repeat(indent);
+    body.lines()
+        .enumerate()
+        .map(|(index, line)| match index {
+            0 => format!("{}{}", marker, line),
+            _ if line.is_empty() => String::new(),
+            _ => format!("{}{}", padding, line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_table(node: &Value, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    let rows: Vec<Vec<String>> = children(node)
+        .iter()
+        .map(|row| {
+            children(row)
+                .iter()
+                .map(|cell| {
+                    children(cell)
+                        .iter()
+                        .map(|block| render_inline(block, resolve_image))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        .replace('|', "\\|")
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut lines = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        lines.push(format!("| {} |", row.join(" | ")));
+        if index == 0 {
+            lines.push(format!("|{}", " --- |".repeat(row.len().max(1))));
+        }
+    }
+    lines.join("\n")
+}
+
+fn render_image(node: &Value, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    let src = node.pointer("/attrs/src").and_then(Value::as_str).unwrap_or_default();
+    let alt = node.pointer("/attrs/alt").and_then(Value::as_str).unwrap_or_default();
+    format!("![{}]({})", alt, resolve_image(src))
+}
+
+fn render_inline(node: &Value, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    let mut out = String::new();
+    for child in children(node) {
+        match node_type(child) {
+            "text" => {
+                let text = child.get("text").and_then(Value::as_str).unwrap_or_default();
+                out.push_str(&apply_marks(text, child.get("marks").and_then(Value::as_array)));
+            }
+            "hardBreak" => out.push_str("  \n"),
+            "image" => out.push_str(&render_image(child, resolve_image)),
+            _ => out.push_str(&render_inline(child, resolve_image)),
+        }
+    }
+    out
+}
+
+/// Wrap text in Markdown for its marks, keeping surrounding whitespace outside the markers
+fn apply_marks(text: &str, marks: Option<&Vec<Value>>) -> String {
+    let Some(marks) = marks.filter(|marks| !marks.is_empty()) else {
+        return text.to_string();
+    };
+    let core = text.trim();
+    if core.is_empty() {
+        return text.to_string();
+    }
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+
+    let has = |name: &str| marks.iter().any(|mark| node_type(mark) == name);
+    let mut core = core.to_string();
+    if has("code") {
+        core = format!("`{}`", core);
+    }
+    if has("bold") {
+        core = format!("**{}**", core);
+    }
+    if has("italic") {
+        core = format!("*{}*", core);
+    }
+    if has("strike") {
+        core = format!("~~{}~~", core);
+    }
+    if let Some(href) = marks
+        .iter()
+        .find(|mark| node_type(mark) == "link")
+        .and_then(|mark| mark.pointer("/attrs/href"))
+        .and_then(Value::as_str)
+    {
+        core = format!("[{}]({})", core, href);
+    }
+    format!("{}{}{}", leading, core, trailing)
+}
+
+/// Escape text for HTML content and attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Convert a tiptap JSON document to HTML
+/// `resolve_image` maps each image `src` to the URL the HTML should use. Content that
+/// isn't a tiptap document is escaped and kept as preformatted text
+pub fn tiptap_to_html(content: &str, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    let doc: Value = match serde_json::from_str(content) {
+        Ok(doc @ Value::Object(_)) if doc.get("type").is_some() => doc,
+        _ => return format!("<pre>{}</pre>", escape_html(content)),
+    };
+
+    html_block(&doc, resolve_image)
+}
+
+fn html_blocks(node: &Value, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    children(node)
+        .iter()
+        .map(|child| html_block(child, resolve_image))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn html_block(node: &Value, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    match node_type(node) {
+        "doc" => html_blocks(node, resolve_image),
+        "paragraph" => format!("<p>{}</p>", html_inline(node, resolve_image)),
+        "heading" => {
+            let level = node.pointer("/attrs/level").and_then(Value::as_u64).unwrap_or(1).clamp(1, 6);
+            format!("<h{0}>{1}</h{0}>", level, html_inline(node, resolve_image))
+        }
+        "bulletList" => format!("<ul>\n{}\n</ul>", html_blocks(node, resolve_image)),
+        "orderedList" => {
+            let start = node.pointer("/attrs/start").and_then(Value::as_u64).unwrap_or(1);
+            let start = if start == 1 { String::new() } else { format!(" start=\"{}\"", start) };
+            format!("<ol{}>\n{}\n</ol>", start, html_blocks(node, resolve_image))
+        }
+        "taskList" => format!("<ul class=\"tasks\">\n{}\n</ul>", html_blocks(node, resolve_image)),
+        "listItem" => format!("<li>{}</li>", html_blocks(node, resolve_image)),
+        "taskItem" => {
+            let checked = node.pointer("/attrs/checked").and_then(Value::as_bool).unwrap_or(false);
+            format!(
+                "<li><input type=\"checkbox\" disabled{}> {}</li>",
+                if checked { " checked" } else { "" },
+                html_blocks(node, resolve_image)
+            )
+        }
+        "blockquote" => format!("<blockquote>\n{}\n</blockquote>", html_blocks(node, resolve_image)),
+        "codeBlock" => {
+            let language = node.pointer("/attrs/language").and_then(Value::as_str).unwrap_or_default();
+            let class = if language.is_empty() {
+                String::new()
+            } else {
+                format!(" class=\"language-{}\"", escape_html(language))
+            };
+            let mut code = String::new();
+            collect_inline(node, &mut code);
+            format!("<pre><code{}>{}</code></pre>", class, escape_html(&code))
+        }
+        "horizontalRule" => "<hr>".to_string(),
+        "image" => html_image(node, resolve_image),
+        "table" => format!("<table>\n{}\n</table>", html_blocks(node, resolve_image)),
+        "tableRow" => format!("<tr>{}</tr>", html_blocks(node, resolve_image)),
+        "tableHeader" => format!("<th>{}</th>", html_blocks(node, resolve_image)),
+        "tableCell" => format!("<td>{}</td>", html_blocks(node, resolve_image)),
+        _ => html_inline(node, resolve_image),
+    }
+}
+
+fn html_image(node: &Value, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    let src = node.pointer("/attrs/src").and_then(Value::as_str).unwrap_or_default();
+    let alt = node.pointer("/attrs/alt").and_then(Value::as_str).unwrap_or_default();
+    format!("<img src=\"{}\" alt=\"{}\">", escape_html(&resolve_image(src)), escape_html(alt))
+}
+
+fn html_inline(node: &Value, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    let mut out = String::new();
+    for child in children(node) {
+        match node_type(child) {
+            "text" => {
+                let text = child.get("text").and_then(Value::as_str).unwrap_or_default();
+                out.push_str(&html_marks(&escape_html(text), child.get("marks").and_then(Value::as_array)));
+            }
+            "hardBreak" => out.push_str("<br>"),
+            "image" => out.push_str(&html_image(child, resolve_image)),
+            _ => out.push_str(&html_inline(child, resolve_image)),
+        }
+    }
+    out
+}
+
+/// Wrap already escaped text in the HTML elements for its marks
+fn html_marks(text: &str, marks: Option<&Vec<Value>>) -> String {
+    let mut html = text.to_string();
+    for mark in marks.map(Vec::as_slice).unwrap_or_default() {
+        html = match node_type(mark) {
+            "bold" => format!("<strong>{}</strong>", html),
+            "italic" => format!("<em>{}</em>", html),
+            "strike" => format!("<s>{}</s>", html),
+            "underline" => format!("<u>{}</u>", html),
+            "code" => format!("<code>{}</code>", html),
+            "link" => match mark.pointer("/attrs/href").and_then(Value::as_str) {
+                Some(href) if is_safe_href(href) => format!("<a href=\"{}\">{}</a>", escape_html(href), html),
+                _ => html,
+            },
+            _ => html,
+        };
+    }
+    html
+}
+
+/// Whether a link can go in exported HTML: http, https and mailto URLs, fragments and
+/// relative paths. Any other scheme (`javascript:`, `data:`...) could run when the page is opened
+fn is_safe_href(href: &str) -> bool {
+    let href = href.trim();
+    let scheme_end = href.find([':', '/', '?', '#']);
+    match scheme_end {
+        Some(end) if href[end..].starts_with(':') => {
+            matches!(href[..end].to_ascii_lowercase().as_str(), "http" | "https" | "mailto")
+        }
+        _ => true,
+    }
+}
+
+/// A node being built while converting Markdown
+struct Frame {
+    node: Value,
+    /// Paragraph opened only to hold inline content (tight list items, table cells...)
+    implicit: bool,
+}
+
+fn new_node(node_type: &str, attrs: Option<Value>) -> Value {
+    let mut node = serde_json::json!({ "type": node_type, "content": [] });
+    if let Some(attrs) = attrs {
+        node["attrs"] = attrs;
+    }
+    node
+}
+
+fn append_child(parent: &mut Value, child: Value) {
+    if let Some(content) = parent.get_mut("content").and_then(Value::as_array_mut) {
+        content.push(child);
+    }
+}
+
+fn close_implicit(stack: &mut Vec<Frame>) {
+    if stack.len() > 1 && stack.last().is_some_and(|frame| frame.implicit) {
+        let frame = stack.pop().expect("stack is not empty");
+        append_child(&mut stack.last_mut().expect("doc frame").node, frame.node);
+    }
+}
+
+fn open_block(stack: &mut Vec<Frame>, node: Value) {
+    close_implicit(stack);
+    stack.push(Frame { node, implicit: false });
+}
+
+fn close_block(stack: &mut Vec<Frame>) {
+    close_implicit(stack);
+    if stack.len() > 1 {
+        let mut node = stack.pop().expect("stack is not empty").node;
+        if node_type(&node) == "codeBlock" {
+            // The parser ends code with a newline tiptap doesn't keep
+            if let Some(text) = node.pointer_mut("/content/0/text") {
+                let trimmed = text.as_str().unwrap_or_default().trim_end_matches('\n').to_string();
+                *text = Value::String(trimmed);
+            }
+            if node.pointer("/content/0/text").and_then(Value::as_str) == Some("") {
+                node["content"] = Value::Array(Vec::new());
+            }
+        }
+        append_child(&mut stack.last_mut().expect("doc frame").node, node);
+    }
+}
+
+/// Open a paragraph if the current block can't hold inline content
+fn ensure_inline_container(stack: &mut Vec<Frame>) {
+    let top = stack.last().map(|frame| node_type(&frame.node)).unwrap_or_default();
+    if !matches!(top, "paragraph" | "heading" | "codeBlock") {
+        stack.push(Frame { node: new_node("paragraph", None), implicit: true });
+    }
+}
+
+fn push_inline(stack: &mut Vec<Frame>, node: Value) {
+    ensure_inline_container(stack);
+    append_child(&mut stack.last_mut().expect("doc frame").node, node);
+}
+
+/// What an inline event adds to an image's alt text, which is plain text with breaks as spaces
+fn alt_text<'a>(event: &'a pulldown_cmark::Event) -> Option<std::borrow::Cow<'a, str>> {
+    use pulldown_cmark::Event;
+
+    match event {
+        Event::Text(text)
+        | Event::Code(text)
+        | Event::InlineMath(text)
+        | Event::DisplayMath(text)
+        | Event::Html(text)
+        | Event::InlineHtml(text) => Some(std::borrow::Cow::Borrowed(text)),
+        Event::SoftBreak | Event::HardBreak => Some(std::borrow::Cow::Borrowed(" ")),
+        Event::FootnoteReference(label) => Some(std::borrow::Cow::Owned(format!("[^{}]", label))),
+        _ => None,
+    }
+}
+
+/// Add `text` to the alt text when the innermost open node is an image
+/// Returns false when it isn't, and the event is handled as usual
+fn push_alt(stack: &mut [Frame], text: &str) -> bool {
+    let Some(top) = stack.last_mut().filter(|frame| node_type(&frame.node) == "image") else {
+        return false;
+    };
+    let alt = top.node["attrs"]["alt"].as_str().unwrap_or_default().to_string();
+    top.node["attrs"]["alt"] = Value::String(alt + text);
+    true
+}
+
+fn text_node(text: &str, marks: &[Value]) -> Value {
+    let mut node = serde_json::json!({ "type": "text", "text": text });
+    if !marks.is_empty() {
+        node["marks"] = Value::Array(marks.to_vec());
+    }
+    node
+}
+
+/// Convert Markdown (CommonMark with tables, strikethrough and task lists) to a tiptap document
+pub fn markdown_to_tiptap(markdown: &str) -> Value {
+    use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut stack = vec![Frame { node: new_node("doc", None), implicit: false }];
+    let mut marks: Vec<Value> = Vec::new();
+    let mut in_table_head = false;
+
+    for event in Parser::new_ext(markdown, options) {
+        if let Some(text) = alt_text(&event) {
+            if push_alt(&mut stack, &text) {
+                continue;
+            }
+        }
+        match event {
+            Event::Start(tag) => match tag {
+                Tag::Paragraph => open_block(&mut stack, new_node("paragraph", None)),
+                Tag::Heading { level, .. } => open_block(
+                    &mut stack,
+                    new_node("heading", Some(serde_json::json!({ "level": level as u8 }))),
+                ),
+                Tag::BlockQuote(_) => open_block(&mut stack, new_node("blockquote", None)),
+                Tag::CodeBlock(kind) => {
+                    let language = match kind {
+                        CodeBlockKind::Fenced(info) => info.split_whitespace().next().map(str::to_string),
+                        CodeBlockKind::Indented => None,
+                    };
+                    open_block(&mut stack, new_node("codeBlock", Some(serde_json::json!({ "language": language }))));
+                }
+                Tag::List(Some(start)) => open_block(
+                    &mut stack,
+                    new_node("orderedList", Some(serde_json::json!({ "start": start }))),
+                ),
+                Tag::List(None) => open_block(&mut stack, new_node("bulletList", None)),
+                Tag::Item => open_block(&mut stack, new_node("listItem", None)),
+                Tag::Table(_) => open_block(&mut stack, new_node("table", None)),
+                Tag::TableHead => {
+                    in_table_head = true;
+                    open_block(&mut stack, new_node("tableRow", None));
+                }
+                Tag::TableRow => open_block(&mut stack, new_node("tableRow", None)),
+                Tag::TableCell => {
+                    let cell = if in_table_head { "tableHeader" } else { "tableCell" };
+                    open_block(&mut stack, new_node(cell, None));
+                }
+                Tag::Emphasis => marks.push(serde_json::json!({ "type": "italic" })),
+                Tag::Strong => marks.push(serde_json::json!({ "type": "bold" })),
+                Tag::Strikethrough => marks.push(serde_json::json!({ "type": "strike" })),
+                Tag::Link { dest_url, .. } => {
+                    marks.push(serde_json::json!({ "type": "link", "attrs": { "href": dest_url.to_string() } }))
+                }
+                Tag::Image { dest_url, title, .. } => {
+                    // Alt text arrives as text events until the image ends
+                    let title = Some(title.to_string()).filter(|t| !t.is_empty());
+                    let image = serde_json::json!({
+                        "type": "image",
+                        "attrs": { "src": dest_url.to_string(), "alt": "", "title": title },
+                    });
+                    // An image inside alt text only adds its own alt text
+                    if node_type(&stack.last().expect("doc frame").node) != "image" {
+                        ensure_inline_container(&mut stack);
+                    }
+                    stack.push(Frame { node: image, implicit: false });
+                }
+                _ => {}
+            },
+            Event::End(tag) => match tag {
+                TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link => {
+                    marks.pop();
+                }
+                TagEnd::Image => {
+                    let image = stack.pop().expect("image frame").node;
+                    let alt = image["attrs"]["alt"].as_str().unwrap_or_default().to_string();
+                    if !push_alt(&mut stack, &alt) {
+                        append_child(&mut stack.last_mut().expect("doc frame").node, image);
+                    }
+                }
+                TagEnd::TableHead => {
+                    close_block(&mut stack);
+                    in_table_head = false;
+                }
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::BlockQuote(_)
+                | TagEnd::CodeBlock
+                | TagEnd::List(_)
+                | TagEnd::Item
+                | TagEnd::Table
+                | TagEnd::TableRow
+                | TagEnd::TableCell => close_block(&mut stack),
+                _ => {}
+            },
+            Event::Text(text) => {
+                let top = stack.last_mut().expect("doc frame");
+                match node_type(&top.node) {
+                    "codeBlock" => {
+                        // Code arrives line by line; keep it in a single text node
+                        match top.node.pointer_mut("/content/0/text") {
+                            Some(Value::String(code)) => code.push_str(&text),
+                            _ => append_child(&mut top.node, text_node(&text, &[])),
+                        }
+                    }
+                    _ => push_inline(&mut stack, text_node(&text, &marks)),
+                }
+            }
+            Event::Code(code) => {
+                let mut code_marks = marks.clone();
+                code_marks.push(serde_json::json!({ "type": "code" }));
+                push_inline(&mut stack, text_node(&code, &code_marks));
+            }
+            Event::InlineMath(text) | Event::DisplayMath(text) | Event::Html(text) | Event::InlineHtml(text) => {
+                push_inline(&mut stack, text_node(&text, &marks))
+            }
+            Event::SoftBreak => push_inline(&mut stack, text_node(" ", &marks)),
+            Event::HardBreak => push_inline(&mut stack, serde_json::json!({ "type": "hardBreak" })),
+            Event::Rule => {
+                close_implicit(&mut stack);
+                append_child(&mut stack.last_mut().expect("doc frame").node, serde_json::json!({ "type": "horizontalRule" }));
+            }
+            Event::TaskListMarker(checked) => {
+                // The marker opens the item: it becomes a task item in a task list
+                close_implicit(&mut stack);
+                let depth = stack.len();
+                if depth >= 2 && node_type(&stack[depth - 1].node) == "listItem" {
+                    stack[depth - 1].node["type"] = Value::from("taskItem");
+                    stack[depth - 1].node["attrs"] = serde_json::json!({ "checked": checked });
+                    let list = &mut stack[depth - 2].node;
+                    list["type"] = Value::from("taskList");
+                    if let Some(attrs) = list.as_object_mut() {
+                        attrs.remove("attrs");
+                    }
+                }
+            }
+            Event::FootnoteReference(label) => push_inline(&mut stack, text_node(&format!("[^{}]", label), &marks)),
+        }
+    }
+
+    while stack.len() > 1 {
+        close_block(&mut stack);
+    }
+    stack.pop().expect("doc frame").node
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn text(text: &str) -> Value {
+        json!({ "type": "text", "text": text })
+    }
+
+    fn paragraph(text_value: &str) -> Value {
+        json!({ "type": "paragraph", "content": [text(text_value)] })
+    }
+
+    fn item(content: Vec<Value>) -> Value {
+        json!({ "type": "listItem", "content": content })
+    }
+
+    fn doc(content: Vec<Value>) -> String {
+        json!({ "type": "doc", "content": content }).to_string()
+    }
+
+    #[test]
+    fn tiptap_to_plaintext_puts_blocks_on_lines_with_list_markers() {
+        let cases = [
+            (
+                doc(vec![json!({ "type": "heading", "attrs": { "level": 1 }, "content": [text("Title")] }), paragraph("Body")]),
+                "Title\nBody",
+            ),
+            (
+                doc(vec![json!({ "type": "paragraph", "content": [text("one"), { "type": "hardBreak" }, text("two")] })]),
+                "one\ntwo",
+            ),
+            (
+                doc(vec![json!({ "type": "bulletList", "content": [item(vec![paragraph("a")]), item(vec![paragraph("b")])] })]),
+                "• a\n• b",
+            ),
+            (
+                doc(vec![json!({ "type": "orderedList", "attrs": { "start": 3 }, "content": [item(vec![paragraph("a")]), item(vec![paragraph("b")])] })]),
+                "3. a\n4. b",
+            ),
+            (
+                doc(vec![json!({ "type": "taskList", "content": [
+                    { "type": "taskItem", "attrs": { "checked": true }, "content": [paragraph("done")] },
+                    { "type": "taskItem", "attrs": { "checked": false }, "content": [paragraph("todo")] },
+                ] })]),
+                "[x] done\n[ ] todo",
+            ),
+            (
+                doc(vec![json!({ "type": "bulletList", "content": [item(vec![
+                    paragraph("outer"),
+                    json!({ "type": "bulletList", "content": [item(vec![
+                        paragraph("inner"),
+                        json!({ "type": "orderedList", "content": [item(vec![paragraph("deepest")])] }),
+                    ])] }),
+                ])] })]),
+                "• outer\n  • inner\n    1. deepest",
+            ),
+            (doc(vec![paragraph("   "), paragraph("kept")]), "kept"),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(tiptap_to_plaintext(&content), expected, "{}", content);
+        }
+    }
+
+    #[test]
+    fn tiptap_to_plaintext_returns_other_content_unchanged() {
+        for content in ["{not json", "plain text", "[1, 2]", "{\"text\":\"no type\"}", ""] {
+            assert_eq!(tiptap_to_plaintext(content), content);
+        }
+    }
+
+    #[test]
+    fn tiptap_to_markdown_renders_each_block() {
+        let cases = [
+            (
+                doc(vec![json!({ "type": "heading", "attrs": { "level": 2 }, "content": [text("Title")] }), paragraph("Body")]),
+                "## Title\n\nBody",
+            ),
+            (
+                doc(vec![json!({ "type": "paragraph", "content": [
+                    { "type": "text", "text": "bold ", "marks": [{ "type": "bold" }] },
+                    { "type": "text", "text": "site", "marks": [{ "type": "link", "attrs": { "href": "https://example.com" } }] },
+                ] })]),
+                "**bold** [site](https://example.com)",
+            ),
+            (
+                doc(vec![json!({ "type": "bulletList", "content": [item(vec![
+                    paragraph("outer"),
+                    json!({ "type": "bulletList", "content": [item(vec![paragraph("inner")])] }),
+                ])] })]),
+                "- outer\n  - inner",
+            ),
+            (
+                doc(vec![json!({ "type": "codeBlock", "attrs": { "language": "rust" }, "content": [text("let a = 1;")] })]),
+                "```rust\nlet a = 1;\n```",
+            ),
+            (
+                doc(vec![json!({ "type": "image", "attrs": { "src": "images/1_a.png", "alt": "A" } })]),
+                "![A](assets/images/1_a.png)",
+            ),
+        ];
+        for (content, expected) in cases {
+            let markdown = tiptap_to_markdown(&content, &mut |src| format!("assets/{}", src));
+            assert_eq!(markdown, expected, "{}", content);
+        }
+        assert_eq!(tiptap_to_markdown("{not json", &mut |src| src.to_string()), "{not json");
+    }
+
+    #[test]
+    fn markdown_round_trips_through_tiptap() {
+        let cases = [
+            "# Title\n\nSome **bold**, *italic*, ~~struck~~ and `code` text",
+            "[a link](https://example.com)",
+            "- one\n- two\n  - nested",
+            "3. three\n4. four",
+            "- [x] done\n- [ ] todo",
+            "> quoted\n>\n> twice",
+            "```rust\nfn main() {}\n```",
+            "| a | b |\n| --- | --- |\n| 1 | 2 |",
+            "before\n\n---\n\nafter",
+            "![alt](images/1_a.png)",
+        ];
+        for markdown in cases {
+            let doc = markdown_to_tiptap(markdown).to_string();
+            assert_eq!(tiptap_to_markdown(&doc, &mut |src| src.to_string()), markdown, "{}", doc);
+        }
+    }
+
+    #[test]
+    fn markdown_to_tiptap_builds_task_lists_and_code_blocks() {
+        let doc = markdown_to_tiptap("- [x] done\n\n```js\nx\n```");
+        assert_eq!(doc["content"][0]["type"], "taskList");
+        assert_eq!(doc["content"][0]["content"][0]["type"], "taskItem");
+        assert_eq!(doc["content"][0]["content"][0]["attrs"]["checked"], true);
+        assert_eq!(doc["content"][1]["type"], "codeBlock");
+        assert_eq!(doc["content"][1]["attrs"]["language"], "js");
+    }
+
+    #[test]
+    fn tiptap_to_html_renders_and_escapes_each_block() {
+        let cases = [
+            (doc(vec![paragraph("<script>alert('x')</script>")]), "<p>&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;</p>"),
+            (
+                doc(vec![json!({ "type": "heading", "attrs": { "level": 9 }, "content": [text("Deep")] })]),
+                "<h6>Deep</h6>",
+            ),
+            (
+                doc(vec![json!({ "type": "orderedList", "attrs": { "start": 2 }, "content": [item(vec![paragraph("a")])] })]),
+                "<ol start=\"2\">\n<li><p>a</p></li>\n</ol>",
+            ),
+            (
+                doc(vec![json!({ "type": "taskList", "content": [{ "type": "taskItem", "attrs": { "checked": true }, "content": [paragraph("done")] }] })]),
+                "<ul class=\"tasks\">\n<li><input type=\"checkbox\" disabled checked> <p>done</p></li>\n</ul>",
+            ),
+            (
+                doc(vec![json!({ "type": "codeBlock", "attrs": { "language": "rust" }, "content": [text("a < b")] })]),
+                "<pre><code class=\"language-rust\">a &lt; b</code></pre>",
+            ),
+            (
+                doc(vec![json!({ "type": "paragraph", "content": [
+                    { "type": "text", "text": "b", "marks": [{ "type": "bold" }, { "type": "italic" }] },
+                    { "type": "image", "attrs": { "src": "images/1_a.png", "alt": "\"A\"" } },
+                ] })]),
+                "<p><em><strong>b</strong></em><img src=\"assets/images/1_a.png\" alt=\"&quot;A&quot;\"></p>",
+            ),
+            ("{not json".to_string(), "<pre>{not json</pre>"),
+        ];
+        for (content, expected) in cases {
+            let html = tiptap_to_html(&content, &mut |src| format!("assets/{}", src));
+            assert_eq!(html, expected, "{}", content);
+        }
+    }
+
+    #[test]
+    fn markdown_image_alt_text_takes_every_inline_event() {
+        let cases = [
+            ("![a `b` c](x.png)", "a b c"),
+            ("![two\nlines](x.png)", "two lines"),
+            ("![a <b>bold</b> c](x.png)", "a <b>bold</b> c"),
+            ("![*a* ![b](y.png)](x.png)", "a b"),
+        ];
+        for (markdown, alt) in cases {
+            let doc = markdown_to_tiptap(markdown);
+            let paragraph = &doc["content"][0];
+            assert_eq!(paragraph["type"], "paragraph", "{}", markdown);
+            assert_eq!(paragraph["content"].as_array().map(Vec::len), Some(1), "{}", markdown);
+            assert_eq!(paragraph["content"][0]["type"], "image", "{}", markdown);
+            assert_eq!(paragraph["content"][0]["attrs"]["src"], "x.png", "{}", markdown);
+            assert_eq!(paragraph["content"][0]["attrs"]["alt"], alt, "{}", markdown);
+        }
+    }
+
+    #[test]
+    fn tiptap_to_html_keeps_only_safe_links() {
+        let cases = [
+            ("https://example.com/a?b#c", true),
+            ("HTTP://example.com", true),
+            ("mailto:me@example.com", true),
+            ("#heading", true),
+            ("notes/other.html", true),
+            ("../up.html?x=a:b", true),
+            ("javascript:alert(1)", false),
+            ("  JavaScript:alert(1)", false),
+            ("java\tscript:alert(1)", false),
+            ("data:text/html,<script>alert(1)</script>", false),
+            ("vbscript:msgbox", false),
+            ("file:///etc/passwd", false),
+        ];
+        for (href, kept) in cases {
+            let content = doc(vec![json!({ "type": "paragraph", "content": [
+                { "type": "text", "text": "x", "marks": [{ "type": "link", "attrs": { "href": href } }] },
+            ] })]);
+            let html = tiptap_to_html(&content, &mut |src| src.to_string());
+            let expected = if kept { format!("<p><a href=\"{}\">x</a></p>", escape_html(href)) } else { "<p>x</p>".to_string() };
+            assert_eq!(html, expected, "{}", href);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use tauri::{command, State};
+
+use super::content::{card_plaintext, card_preview};
+use crate::db::AppState;
+use crate::error::CommandError;
+
+// ============================================
+// Dashboard Commands
+// ============================================
+
+/// Upper bound on how many recent items a single call returns
+const MAX_RECENT_ITEMS: i64 = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentItem {
+    pub entity_type: String, // card or board
+    pub id: String,
+    pub title: Option<String>,
+    pub preview: Option<String>,
+    pub updated_at: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct RecentRow {
+    entity_type: String,
+    id: String,
+    title: Option<String>,
+    preview: Option<String>,
+    content: Option<String>, // only for cards saved before previews were stored
+    content_type: Option<String>,
+    updated_at: i64,
+}
+
+/// Get the most recently edited cards and boards, newest first
+/// Trashed cards are left out; `limit` is capped at `MAX_RECENT_ITEMS`
+#[command]
+pub async fn get_recent_items(state: State<'_, AppState>, limit: i64) -> Result<Vec<RecentItem>, CommandError> {
+    let limit = limit.clamp(0, MAX_RECENT_ITEMS);
+
+    let rows = sqlx::query_as::<_, RecentRow>(
+        "SELECT 'card' AS entity_type, id, title, preview,
+                CASE WHEN preview IS NULL THEN content END AS content, content_type, updated_at
+         FROM cards WHERE deleted_at IS NULL
+         UNION ALL
+         SELECT 'board' AS entity_type, id, title, NULL AS preview, NULL AS content, NULL AS content_type, updated_at
+         FROM boards
+         ORDER BY updated_at DESC
+         LIMIT ?",
+    )
+    .bind(limit)
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get recent items", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| RecentItem {
+            entity_type: row.entity_type,
+            id: row.id,
+            title: row.title,
+            preview: row.preview.or_else(|| {
+                let content = row.content?;
+                card_preview(&card_plaintext(row.content_type.as_deref().unwrap_or("tiptap"), &content))
+            }),
+            updated_at: row.updated_at,
+        })
+        .collect())
+}
@@ -1,28 +1,299 @@
 use serde::{Deserialize, Serialize};
-use tauri::{command, Manager};
+use tauri::{command, Manager, State};
 
+use super::app_state::assets_root;
+use super::cards::{card_columns, Card};
+use super::search;
+use crate::db::AppState;
+use crate::error::CommandError;
+
+
+// ============================================
+// Database Commands
+// ============================================
+
+fn is_same_file(a: &std::path::Path, b: &std::path::Path) -> bool {
+    a == b || matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+}
+
+/// Open the SQLite database at `db_path` (creating the file and schema if needed)
+/// and make it the database every command uses. The previous pools are closed.
+/// Calling it again for the database that is already open does nothing. Optional:
+/// the default database is already open by the time the frontend can call this
+#[command]
+pub async fn init_database(state: State<'_, AppState>, db_path: String) -> Result<String, CommandError> {
+    let path = std::path::PathBuf::from(&db_path);
+    if is_same_file(&state.path(), &path) {
+        return Ok(db_path);
+    }
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| CommandError::io("Failed to create database directory", e))?;
+    }
+
+    let db = crate::db::connect(&path).await?;
+    // Buffered canvas snapshots belong to the database being closed
+    super::boards::write_pending_snapshots(&state, None).await?;
+    let previous = state.replace(db, path);
+    state.snapshots().discard();
+    previous.close().await;
+
+    log::info!("Database initialized at: {}", db_path);
+    Ok(db_path)
+}
+
+// ============================================
+// Database Maintenance
+// ============================================
+
+/// Size of the database file plus its write-ahead log, in bytes
+fn database_size(db_path: &std::path::Path) -> u64 {
+    let mut wal_path = db_path.as_os_str().to_owned();
+    wal_path.push("-wal");
+    [db_path.to_path_buf(), std::path::PathBuf::from(wal_path)]
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Database size before and after `optimize_database`, in bytes (file plus WAL)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseOptimization {
+    pub size_before: u64,
+    pub size_after: u64,
+    pub reclaimed: u64,
+}
+
+/// Compact the database: merge the FTS index segments, rebuild the file with `VACUUM`
+/// and truncate the WAL. Other commands wait on the busy timeout while it runs,
+/// so call it while the app is idle
+#[command]
+pub async fn optimize_database(state: State<'_, AppState>) -> Result<DatabaseOptimization, CommandError> {
+    let pool = state.writer();
+    let db_path = state.path();
+    let size_before = database_size(&db_path);
+
+    sqlx::query("INSERT INTO search_index(search_index) VALUES('optimize')")
+        .execute(&pool)
+        .await
+        .map_err(|e| CommandError::db("Failed to optimize search index", e))?;
+
+    sqlx::query("VACUUM")
+        .execute(&pool)
+        .await
+        .map_err(|e| CommandError::db("Failed to vacuum database", e))?;
+
+    // Returns (busy, log frames, checkpointed frames); busy means a reader kept the WAL alive
+    let (busy, _, _): (i64, i64, i64) = sqlx::query_as("PRAGMA wal_checkpoint(TRUNCATE)")
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| CommandError::db("Failed to checkpoint database", e))?;
+    if busy != 0 {
+        log::warn!("WAL checkpoint was blocked by an open reader; the log was not truncated");
+    }
+
+    let size_after = database_size(&db_path);
+    log::info!("Database optimized: {} -> {} bytes", size_before, size_after);
+    Ok(DatabaseOptimization {
+        size_before,
+        size_after,
+        reclaimed: size_before.saturating_sub(size_after),
+    })
+}
+
+/// What `flush_all` wrote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlushSummary {
+    pub snapshots_written: usize,
+    /// False when an open reader kept part of the WAL from being copied back; the
+    /// data is still safe in the WAL and is checkpointed on the next open
+    pub checkpointed: bool,
+}
+
+/// Write every buffered canvas snapshot and checkpoint the WAL into the database file
+/// Meant to be awaited while the app closes; does nothing harmful when nothing is pending
+#[command]
+pub async fn flush_all(state: State<'_, AppState>) -> Result<FlushSummary, CommandError> {
+    let snapshots_written = super::boards::write_pending_snapshots(&state, None).await?;
+
+    // Returns (busy, log frames, checkpointed frames), as in `optimize_database`
+    let (busy, _, _): (i64, i64, i64) = sqlx::query_as("PRAGMA wal_checkpoint(TRUNCATE)")
+        .fetch_one(&state.writer())
+        .await
+        .map_err(|e| CommandError::db("Failed to checkpoint database", e))?;
+    if busy != 0 {
+        log::warn!("WAL checkpoint was blocked by an open reader; the log was not truncated");
+    }
+
+    log::info!("Flushed database ({} pending canvas snapshots)", snapshots_written);
+    Ok(FlushSummary { snapshots_written, checkpointed: busy == 0 })
+}
+
+/// Schema and health of the open database, for the diagnostics panel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbDiagnostics {
+    pub schema_version: i64,
+    pub latest_schema_version: i64, // what this build of the app migrates to
+    pub integrity_check: Vec<String>, // ["ok"] when healthy
+    pub journal_mode: String,
+    pub foreign_keys: bool,
+    pub table_row_counts: std::collections::BTreeMap<String, i64>,
+    pub file_size: u64, // file plus WAL, in bytes
+}
+
+/// Report the schema version, `PRAGMA integrity_check`, connection settings, the row
+/// count of every table and the file size. Only reads, on a reader connection
+#[command]
+pub async fn get_db_diagnostics(state: State<'_, AppState>) -> Result<DbDiagnostics, CommandError> {
+    let mut conn = state.pool()
+        .acquire()
+        .await
+        .map_err(|e| CommandError::db("Failed to get a connection", e))?;
+
+    let schema_version: i64 = sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to read schema version", e))?;
+    let integrity_check: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to check database integrity", e))?;
+    let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to read journal mode", e))?;
+    let foreign_keys: bool = sqlx::query_scalar("PRAGMA foreign_keys")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to read foreign key setting", e))?;
+
+    // FTS shadow tables are internal to the search index and left out
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM pragma_table_list
+         WHERE schema = 'main' AND type IN ('table', 'virtual') AND name NOT LIKE 'sqlite_%'",
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| CommandError::db("Failed to list tables", e))?;
+    let mut table_row_counts = std::collections::BTreeMap::new();
+    for table in tables {
+        let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM \"{}\"", table.replace('"', "\"\"")))
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| CommandError::db(&format!("Failed to count rows of {}", table), e))?;
+        table_row_counts.insert(table, count);
+    }
+
+    Ok(DbDiagnostics {
+        schema_version,
+        latest_schema_version: crate::db::migrations::latest_version(),
+        integrity_check,
+        journal_mode,
+        foreign_keys,
+        table_row_counts,
+        file_size: database_size(&state.path()),
+    })
+}
 
 // ============================================
 // Asset Management Commands
 // ============================================
 
+/// Resolve `relative_path` inside `assets_dir`, rejecting anything that would escape it
+/// Absolute paths and `..` components are refused outright; the nearest existing
+/// ancestor is then canonicalized so symlinks pointing outside the folder are caught too
+pub(crate) fn resolve_asset_path(
+    assets_dir: &std::path::Path,
+    relative_path: &str,
+) -> Result<std::path::PathBuf, CommandError> {
+    use std::path::{Component, Path};
+
+    let relative = Path::new(relative_path);
+    let is_plain_relative = relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if relative_path.is_empty() || !is_plain_relative {
+        log::warn!("Rejected asset path: {}", relative_path);
+        return Err(CommandError::Validation("invalid path".to_string()));
+    }
+
+    std::fs::create_dir_all(assets_dir)
+        .map_err(|e| CommandError::io("Failed to create assets directory", e))?;
+    let assets_root = assets_dir
+        .canonicalize()
+        .map_err(|e| CommandError::io("Failed to resolve assets directory", e))?;
+
+    let path = assets_dir.join(relative);
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .ok_or_else(|| CommandError::Validation("invalid path".to_string()))?
+        .canonicalize()
+        .map_err(|e| CommandError::io("Failed to resolve asset path", e))?;
+    if !existing.starts_with(&assets_root) {
+        log::warn!("Rejected asset path outside assets folder: {}", relative_path);
+        return Err(CommandError::Validation("invalid path".to_string()));
+    }
+
+    Ok(path)
+}
+
+// ============================================
+// Disk Space
+// ============================================
+
+/// Space left free on the assets volume after an import, in bytes
+const DISK_SPACE_MARGIN: u64 = 100 * 1024 * 1024;
+
+/// Fail with "insufficient disk space" unless `incoming` bytes fit on the volume
+/// holding `dir` with `DISK_SPACE_MARGIN` to spare
+fn ensure_disk_space(dir: &std::path::Path, incoming: u64) -> Result<(), CommandError> {
+    let available = fs2::available_space(dir)
+        .map_err(|e| CommandError::io("Failed to read free disk space", e))?;
+    if incoming.saturating_add(DISK_SPACE_MARGIN) > available {
+        log::warn!("Refused import of {} bytes with {} bytes free", incoming, available);
+        return Err(CommandError::Io(format!(
+            "insufficient disk space: {} bytes needed, {} bytes available",
+            incoming, available
+        )));
+    }
+    Ok(())
+}
+
+/// Get the free space on the volume holding the assets folder, in bytes
+#[command]
+pub async fn get_free_disk_space(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<u64, CommandError> {
+    let assets_dir = assets_root(&app, &state).await?;
+    std::fs::create_dir_all(&assets_dir)
+        .map_err(|e| CommandError::io("Failed to create assets directory", e))?;
+
+    fs2::available_space(&assets_dir).map_err(|e| CommandError::io("Failed to read free disk space", e))
+}
+
 // ============================================
 // Directory Structure Command
 // ============================================
 
 #[command]
-pub async fn ensure_directory_structure(app: tauri::AppHandle) -> Result<bool, String> {
+pub async fn ensure_directory_structure(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<bool, CommandError> {
     use std::fs;
     
     let app_data_dir = app.path()
         .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+        .map_err(|e| CommandError::internal("Failed to get app data dir", e))?;
+    let assets_dir = assets_root(&app, &state).await?;
     
     // Create main directories
     let directories = [
-        app_data_dir.join("assets").join("pdfs"),
-        app_data_dir.join("assets").join("images"),
-        app_data_dir.join("assets").join("other"),
+        assets_dir.join("pdfs"),
+        assets_dir.join("images"),
+        assets_dir.join("other"),
+        assets_dir.join("thumbnails"),
         app_data_dir.join("backups"),
         app_data_dir.join("temp"),
     ];
@@ -30,7 +301,7 @@ pub async fn ensure_directory_structure(app: tauri::AppHandle) -> Result<bool, S
     for dir in &directories {
         if let Err(e) = fs::create_dir_all(dir) {
             log::error!("Failed to create directory {:?}: {}", dir, e);
-            return Err(format!("Failed to create directory: {}", e));
+            return Err(CommandError::io("Failed to create directory", e));
         }
     }
     
@@ -38,39 +309,450 @@ pub async fn ensure_directory_structure(app: tauri::AppHandle) -> Result<bool, S
     Ok(true)
 }
 
-/// Get the assets directory path
+/// Get the app data directory path
 #[command]
-pub async fn get_assets_dir(app: tauri::AppHandle) -> Result<String, String> {
+pub async fn get_app_data_dir(app: tauri::AppHandle) -> Result<String, CommandError> {
     let app_data_dir = app.path()
         .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+        .map_err(|e| CommandError::internal("Failed to get app data dir", e))?;
     
-    let assets_dir = app_data_dir.join("assets");
+    Ok(app_data_dir.to_string_lossy().to_string())
+}
+
+/// Get the assets directory path
+#[command]
+pub async fn get_assets_dir(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<String, CommandError> {
+    let assets_dir = assets_root(&app, &state).await?;
     Ok(assets_dir.to_string_lossy().to_string())
 }
 
-/// Copy a file to the app's assets folder
-/// Returns the new relative path within the assets folder
+/// Metadata about a file stored in the assets folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetInfo {
+    pub relative_path: String,
+    pub file_size: i64,
+    pub mime_type: String,
+    /// "pdf", "image", "audio", "video", "text", "archive" or "unknown"
+    pub detected_kind: String,
+    pub thumbnail_path: Option<String>,
+}
+
+/// Bytes read to decide whether a file without a known signature is text
+const TEXT_SNIFF_BYTES: usize = 8192;
+
+/// Sniff a file's MIME type and kind (see `AssetInfo::detected_kind`) from its contents
+/// Files without a binary signature count as text when their start is valid UTF-8
+/// without null bytes; the extension then picks the text MIME type
+fn sniff_file(path: &std::path::Path) -> std::io::Result<(String, &'static str)> {
+    use infer::MatcherType;
+    use std::io::Read;
+
+    if let Some(kind) = infer::get_from_path(path)? {
+        let detected_kind = match (kind.mime_type(), kind.matcher_type()) {
+            ("application/pdf", _) => "pdf",
+            (_, MatcherType::Image) => "image",
+            (_, MatcherType::Audio) => "audio",
+            (_, MatcherType::Video) => "video",
+            (_, MatcherType::Text) => "text",
+            (_, MatcherType::Archive) => "archive",
+            _ => "unknown",
+        };
+        return Ok((kind.mime_type().to_string(), detected_kind));
+    }
+
+    let mut head = Vec::with_capacity(TEXT_SNIFF_BYTES);
+    std::fs::File::open(path)?
+        .take(TEXT_SNIFF_BYTES as u64)
+        .read_to_end(&mut head)?;
+    // The sample may end inside a multi-byte character
+    let is_text = match std::str::from_utf8(&head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    } && !head.contains(&0);
+    if !is_text {
+        return Ok(("application/octet-stream".to_string(), "unknown"));
+    }
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let mime_type = match extension.as_str() {
+        "md" | "markdown" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        _ => "text/plain",
+    };
+    Ok((mime_type.to_string(), "text"))
+}
+
+/// Build asset info for a stored file, sniffing its MIME type from the contents
+fn read_asset_info(path: &std::path::Path, relative_path: &str) -> Result<AssetInfo, CommandError> {
+    let file_size = std::fs::metadata(path)
+        .map_err(|e| CommandError::io("Failed to read file metadata", e))?
+        .len() as i64;
+    let (mime_type, detected_kind) = sniff_file(path)
+        .map_err(|e| CommandError::io("Failed to read file", e))?;
+
+    Ok(AssetInfo {
+        relative_path: relative_path.to_string(),
+        file_size,
+        mime_type,
+        detected_kind: detected_kind.to_string(),
+        thumbnail_path: None,
+    })
+}
+
+/// Longest edge of generated thumbnails, in pixels
+const THUMBNAIL_MAX_SIZE: u32 = 256;
+
+/// Relative path of the thumbnail belonging to an image asset
+fn thumbnail_relative_path(relative_path: &str) -> String {
+    let file_name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+    format!("thumbnails/{}.png", file_name)
+}
+
+/// Decode an image and write a downscaled PNG copy of it
+fn write_thumbnail(source: &std::path::Path, target: &std::path::Path) -> Result<(), CommandError> {
+    let image = image::ImageReader::open(source)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|e| CommandError::io("Failed to open image", e))?
+        .decode()
+        .map_err(|e| CommandError::internal("Failed to decode image", e))?;
+
+    // Only ever scale down; `thumbnail` keeps the aspect ratio
+    let thumbnail = if image.width().max(image.height()) > THUMBNAIL_MAX_SIZE {
+        image.thumbnail(THUMBNAIL_MAX_SIZE, THUMBNAIL_MAX_SIZE)
+    } else {
+        image
+    };
+
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| CommandError::io("Failed to create thumbnails directory", e))?;
+    }
+    thumbnail
+        .save_with_format(target, image::ImageFormat::Png)
+        .map_err(|e| CommandError::internal("Failed to write thumbnail", e))
+}
+
+/// Generate the thumbnail for an image asset
+/// Returns None (and logs) when the image is unsupported or corrupt
+fn generate_thumbnail(assets_dir: &std::path::Path, relative_path: &str) -> Option<String> {
+    let thumbnail_path = thumbnail_relative_path(relative_path);
+
+    let result = resolve_asset_path(assets_dir, relative_path).and_then(|source| {
+        let target = resolve_asset_path(assets_dir, &thumbnail_path)?;
+        write_thumbnail(&source, &target)
+    });
+
+    match result {
+        Ok(()) => {
+            log::info!("Generated thumbnail: {}", thumbnail_path);
+            Some(thumbnail_path)
+        }
+        Err(e) => {
+            log::warn!("Skipping thumbnail for {}: {}", relative_path, e);
+            None
+        }
+    }
+}
+
+/// Existing thumbnail for an image asset, generating it if missing
+async fn ensure_thumbnail(assets_dir: std::path::PathBuf, relative_path: String) -> Option<String> {
+    let thumbnail_path = thumbnail_relative_path(&relative_path);
+    if matches!(resolve_asset_path(&assets_dir, &thumbnail_path), Ok(path) if path.exists()) {
+        return Some(thumbnail_path);
+    }
+
+    // Decoding large images is CPU heavy, keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || generate_thumbnail(&assets_dir, &relative_path))
+        .await
+        .ok()
+        .flatten()
+}
+
+/// SHA-256 of a file's contents as a hex string, read in chunks
+pub(crate) fn hash_file(path: &std::path::Path) -> Result<String, CommandError> {
+    use sha2::{Digest, Sha256};
+    use std::fs::File;
+    use std::io;
+
+    let mut file = File::open(path)
+        .map_err(|e| CommandError::io("Failed to open file", e))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .map_err(|e| CommandError::io("Failed to hash file", e))?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Most numbered names `create_asset_file` tries before giving up
+const MAX_ASSET_NAME_ATTEMPTS: u32 = 1000;
+
+/// Create an empty file for a new asset at `relative_path` (`subdir/timestamp_name`)
+/// When an import in the same millisecond already took that name, `timestamp-1_name`,
+/// `timestamp-2_name`, ... are tried in turn. Each attempt uses `create_new`, so two
+/// imports never end up with the same file. Returns the relative and full path that
+/// were claimed; the caller then writes the content over the empty file
+fn create_asset_file(
+    assets_dir: &std::path::Path,
+    relative_path: &str,
+) -> Result<(String, std::path::PathBuf), CommandError> {
+    let (subdir, file_name) = match relative_path.rsplit_once('/') {
+        Some((subdir, file_name)) => (Some(subdir), file_name),
+        None => (None, relative_path),
+    };
+    let (prefix, name) = file_name.split_once('_').unwrap_or((file_name, ""));
+
+    for attempt in 0..MAX_ASSET_NAME_ATTEMPTS {
+        let candidate = match attempt {
+            0 => file_name.to_string(),
+            n => format!("{}-{}_{}", prefix, n, name),
+        };
+        let candidate = match subdir {
+            Some(subdir) => format!("{}/{}", subdir, candidate),
+            None => candidate,
+        };
+        let path = resolve_asset_path(assets_dir, &candidate)?;
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => return Ok((candidate, path)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(CommandError::io("Failed to create asset file", e)),
+        }
+    }
+    Err(CommandError::Conflict(format!("No free file name for asset: {}", relative_path)))
+}
+
+/// Copy a file into the library, reusing identical content that is already there
+/// Returns the asset's relative path. Newly written files are pushed to `written`
+/// so a failed import can remove them again
+pub(crate) async fn import_asset_copy(
+    conn: &mut sqlx::SqliteConnection,
+    assets_dir: &std::path::Path,
+    source: &std::path::Path,
+    file_type: &str,
+    written: &mut Vec<std::path::PathBuf>,
+) -> Result<String, CommandError> {
+    let hash = hash_file(source)?;
+    let existing: Option<String> = sqlx::query_scalar("SELECT relative_path FROM assets WHERE hash = ?")
+        .bind(&hash)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to look up asset", e))?;
+    if let Some(existing) = existing {
+        if resolve_asset_path(assets_dir, &existing)?.exists() {
+            return Ok(existing);
+        }
+    }
+
+    let subdir = match file_type {
+        "pdf" => "pdfs",
+        "image" => "images",
+        _ => "other",
+    };
+    let name = source.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let subdir_path = resolve_asset_path(assets_dir, subdir)?;
+    std::fs::create_dir_all(&subdir_path)
+        .map_err(|e| CommandError::io("Failed to create assets directory", e))?;
+    let (relative_path, target) = create_asset_file(assets_dir, &format!("{}/{}_{}", subdir, timestamp, name))?;
+    written.push(target.clone());
+    std::fs::copy(source, &target)
+        .map_err(|e| CommandError::io(&format!("Failed to copy asset {}", source.display()), e))?;
+    if file_type == "image" {
+        if let Some(thumbnail) = ensure_thumbnail(assets_dir.to_path_buf(), relative_path.clone()).await {
+            written.push(assets_dir.join(thumbnail));
+        }
+    }
+
+    let file_size = std::fs::metadata(&target).map(|m| m.len() as i64).ok();
+    let mime_type = sniff_file(&target).ok().map(|(mime_type, _)| mime_type);
+    let asset_id: String = sqlx::query_scalar(
+        "INSERT INTO assets (id, hash, relative_path, file_type, file_size, mime_type, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(hash) DO UPDATE SET relative_path = excluded.relative_path
+         RETURNING id",
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(&hash)
+    .bind(&relative_path)
+    .bind(file_type)
+    .bind(file_size)
+    .bind(mime_type.unwrap_or_else(|| "application/octet-stream".to_string()))
+    .bind(timestamp)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| CommandError::db("Failed to record asset", e))?;
+
+    search::index_entity(conn, "file", &asset_id, asset_display_name(&relative_path), "", "")
+        .await
+        .map_err(|e| CommandError::db("Failed to index file", e))?;
+
+    Ok(relative_path)
+}
+
+/// Name an asset is shown and searched under: its file name without the import timestamp
+pub(crate) fn asset_display_name(relative_path: &str) -> &str {
+    let file_name = relative_path.rsplit(['/', '\\']).next().unwrap_or(relative_path);
+    match file_name.split_once('_') {
+        // `timestamp-n_` when `create_asset_file` had to number the name
+        Some((prefix, rest)) if !prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_digit() || c == '-') => rest,
+        _ => file_name,
+    }
+}
+
+/// Add an asset to the search index (entity type "file") under its name
+/// PDF text is extracted on a background task, stored on the asset row and indexed
+/// once ready, so imports don't wait on large documents
+async fn index_asset(
+    pool: &sqlx::SqlitePool,
+    asset_id: &str,
+    relative_path: &str,
+    file_path: std::path::PathBuf,
+    file_type: &str,
+) -> Result<(), CommandError> {
+    let name = asset_display_name(relative_path).to_string();
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| CommandError::db("Failed to acquire connection", e))?;
+    search::index_entity(&mut conn, "file", asset_id, &name, "", "")
+        .await
+        .map_err(|e| CommandError::db("Failed to index file", e))?;
+
+    if file_type == "pdf" {
+        let pool = pool.clone();
+        let asset_id = asset_id.to_string();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = index_pdf_text(&pool, &asset_id, &name, file_path).await {
+                log::warn!("Failed to index text of {}: {}", name, e);
+            }
+        });
+    }
+    Ok(())
+}
+
+async fn index_pdf_text(
+    pool: &sqlx::SqlitePool,
+    asset_id: &str,
+    name: &str,
+    file_path: std::path::PathBuf,
+) -> Result<(), CommandError> {
+    let text = tauri::async_runtime::spawn_blocking(move || pdf_extract::extract_text(&file_path))
+        .await
+        .map_err(|e| CommandError::internal("Failed to extract PDF text", e))?
+        .map_err(|e| CommandError::internal("Failed to extract PDF text", e))?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    // The asset may have been deleted while the text was being extracted
+    let updated = sqlx::query("UPDATE assets SET extracted_text = ? WHERE id = ?")
+        .bind(&text)
+        .bind(asset_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to store PDF text", e))?
+        .rows_affected()
+        > 0;
+    if updated {
+        search::index_entity(&mut tx, "file", asset_id, name, &text, "")
+            .await
+            .map_err(|e| CommandError::db("Failed to index file", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Indexed text of {} ({} chars)", name, text.len());
+    Ok(())
+}
+
+/// Move a file, falling back to copy + delete when it can't be renamed (e.g. across filesystems)
+fn move_file(source: &std::path::Path, target: &std::path::Path) -> std::io::Result<()> {
+    use std::fs;
+
+    if fs::rename(source, target).is_ok() {
+        return Ok(());
+    }
+    fs::copy(source, target)?;
+    fs::remove_file(source)
+}
+
+/// Import a file into the app's assets folder
+/// `import_mode` is "copy" (default), "move" or "reference". References leave the
+/// file where it is and return its absolute path instead of a relative one.
+/// Copied/moved files already in the library (same SHA-256) are not stored again
+/// Returns the asset path along with size, MIME type and detected kind.
+/// Copied/moved images also get a thumbnail (at most 256px on the long edge) in `assets/thumbnails`
 #[command]
 pub async fn copy_file_to_assets(
     app: tauri::AppHandle,
+    state: State<'_, AppState>,
     source_path: String,
     file_type: String, // "pdf" | "image"
-) -> Result<String, String> {
+    import_mode: Option<String>, // "copy" | "move" | "reference"
+) -> Result<AssetInfo, CommandError> {
     use std::fs;
     use std::path::Path;
     
+    let import_mode = import_mode.unwrap_or_else(|| "copy".to_string());
+    if !matches!(import_mode.as_str(), "copy" | "move" | "reference") {
+        return Err(CommandError::Validation(format!("Unknown import mode: {}", import_mode)));
+    }
+    
     let source = Path::new(&source_path);
     
     // Validate source exists
     if !source.exists() {
-        return Err(format!("Source file does not exist: {}", source_path));
+        return Err(CommandError::NotFound(format!("Source file does not exist: {}", source_path)));
     }
     
-    // Get app data directory
-    let app_data_dir = app.path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let assets_dir = assets_root(&app, &state).await?;
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    
+    // References only record where the file lives
+    if import_mode == "reference" {
+        let absolute_path = if source.is_absolute() {
+            source.to_path_buf()
+        } else {
+            source.canonicalize()
+                .map_err(|e| CommandError::io("Failed to resolve source path", e))?
+        };
+        let absolute = absolute_path.to_string_lossy().to_string();
+        let info = read_asset_info(&absolute_path, &absolute)?;
+        
+        let asset_id: Option<String> = sqlx::query_scalar(
+            "INSERT INTO assets (id, relative_path, file_type, file_size, mime_type, import_mode, created_at)
+             VALUES (?, ?, ?, ?, ?, 'reference', ?)
+             ON CONFLICT(relative_path) DO NOTHING
+             RETURNING id",
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(&absolute)
+        .bind(&file_type)
+        .bind(info.file_size)
+        .bind(&info.mime_type)
+        .bind(timestamp)
+        .fetch_optional(&state.writer())
+        .await
+        .map_err(|e| CommandError::db("Failed to record asset", e))?;
+        // Already referenced files were indexed the first time
+        if let Some(asset_id) = asset_id {
+            index_asset(&state.writer(), &asset_id, &absolute, absolute_path.clone(), &file_type).await?;
+        }
+        
+        log::info!("Referenced external file: {}", absolute);
+        return Ok(info);
+    }
     
     // Determine target subdirectory
     let subdir = match file_type.as_str() {
@@ -79,49 +761,147 @@ pub async fn copy_file_to_assets(
         _ => "other",
     };
     
-    let assets_dir = app_data_dir.join("assets").join(subdir);
+    // Reuse the existing asset if this content was imported before
+    let hash = hash_file(source)?;
+    let existing: Option<String> = sqlx::query_scalar("SELECT relative_path FROM assets WHERE hash = ?")
+        .bind(&hash)
+        .fetch_optional(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to look up asset", e))?;
+    if let Some(existing) = existing {
+        let existing_path = resolve_asset_path(&assets_dir, &existing)?;
+        if existing_path.exists() {
+            log::info!("Reusing existing asset: {}", existing);
+            // The content is already in the library, so a move only has to drop the source
+            if import_mode == "move" {
+                fs::remove_file(source)
+                    .map_err(|e| CommandError::io("Failed to remove moved file", e))?;
+            }
+            let mut info = read_asset_info(&existing_path, &existing)?;
+            if file_type == "image" {
+                info.thumbnail_path = ensure_thumbnail(assets_dir.clone(), existing).await;
+            }
+            return Ok(info);
+        }
+    }
+    
+    let subdir_path = resolve_asset_path(&assets_dir, subdir)?;
     
     // Create directory if it doesn't exist
-    fs::create_dir_all(&assets_dir)
-        .map_err(|e| format!("Failed to create assets directory: {}", e))?;
+    fs::create_dir_all(&subdir_path)
+        .map_err(|e| CommandError::io("Failed to create assets directory", e))?;
+    
+    // A move within the volume needs no space, but one across volumes is a copy
+    let incoming = fs::metadata(source)
+        .map_err(|e| CommandError::io("Failed to read source file", e))?
+        .len();
+    ensure_disk_space(&subdir_path, incoming)?;
     
     // Generate unique filename: timestamp_originalname
-    let timestamp = chrono::Utc::now().timestamp_millis();
     let original_name = source.file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("file");
-    let new_filename = format!("{}_{}", timestamp, original_name);
+    let (relative_path, target_path) = create_asset_file(
+        &assets_dir,
+        &format!("{}/{}_{}", subdir, timestamp, original_name),
+    )?;
     
-    let target_path = assets_dir.join(&new_filename);
+    // Copy or move the file
+    let stored = if import_mode == "move" {
+        move_file(source, &target_path).map_err(|e| CommandError::io("Failed to move file", e))
+    } else {
+        fs::copy(source, &target_path)
+            .map(|_| ())
+            .map_err(|e| CommandError::io("Failed to copy file", e))
+    };
+    if let Err(e) = stored {
+        let _ = fs::remove_file(&target_path);
+        return Err(e);
+    }
     
-    // Copy the file
-    fs::copy(source, &target_path)
-        .map_err(|e| format!("Failed to copy file: {}", e))?;
+    let mut info = read_asset_info(&target_path, &relative_path)?;
+    if file_type == "image" {
+        info.thumbnail_path = ensure_thumbnail(assets_dir, relative_path.clone()).await;
+    }
     
-    // Return the relative path from assets folder
-    let relative_path = format!("{}/{}", subdir, new_filename);
-    log::info!("Copied file to assets: {}", relative_path);
+    // Track the hash; a stale row whose file went missing now points at the fresh copy
+    let asset_id: String = sqlx::query_scalar(
+        "INSERT INTO assets (id, hash, relative_path, file_type, file_size, mime_type, import_mode, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(hash) DO UPDATE SET relative_path = excluded.relative_path
+         RETURNING id",
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(&hash)
+    .bind(&relative_path)
+    .bind(&file_type)
+    .bind(info.file_size)
+    .bind(&info.mime_type)
+    .bind(&import_mode)
+    .bind(timestamp)
+    .fetch_one(&state.writer())
+    .await
+    .map_err(|e| CommandError::db("Failed to record asset", e))?;
+    index_asset(&state.writer(), &asset_id, &relative_path, target_path.clone(), &file_type).await?;
     
-    Ok(relative_path)
+    log::info!("Imported file to assets ({}): {}", import_mode, relative_path);
+    
+    Ok(info)
 }
 
 /// Delete a file from the assets folder
+/// A file still embedded in a card (see `get_asset_ref_count`) is kept and false is
+/// returned; it can be deleted once the last card using it drops it or is purged
 #[command]
 pub async fn delete_asset_file(
     app: tauri::AppHandle,
+    state: State<'_, AppState>,
     relative_path: String,
-) -> Result<bool, String> {
+) -> Result<bool, CommandError> {
     use std::fs;
     
-    let app_data_dir = app.path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    // Counted on the writer so no card can start using the file in between
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+    let ref_count = asset_ref_count(&mut tx, &relative_path).await?;
+    if ref_count > 0 {
+        log::info!("Kept asset {} (used by {} cards)", relative_path, ref_count);
+        return Ok(false);
+    }
     
-    let file_path = app_data_dir.join("assets").join(&relative_path);
+    // Referenced files live outside the library and are never deleted, only forgotten
+    let removed_reference = sqlx::query(
+        "DELETE FROM assets WHERE relative_path = ? AND import_mode = 'reference'",
+    )
+    .bind(&relative_path)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to remove asset record", e))?
+    .rows_affected()
+        > 0;
+    if removed_reference {
+        tx.commit()
+            .await
+            .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+        log::info!("Removed asset reference: {}", relative_path);
+        return Ok(true);
+    }
+    
+    let file_path = resolve_asset_path(&assets_root(&app, &state).await?, &relative_path)?;
+    sqlx::query("DELETE FROM assets WHERE relative_path = ?")
+        .bind(&relative_path)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to remove asset record", e))?;
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
     
     if file_path.exists() {
         fs::remove_file(&file_path)
-            .map_err(|e| format!("Failed to delete file: {}", e))?;
+            .map_err(|e| CommandError::io("Failed to delete file", e))?;
         log::info!("Deleted asset file: {}", relative_path);
         Ok(true)
     } else {
@@ -131,36 +911,942 @@ pub async fn delete_asset_file(
 }
 
 /// Get the full filesystem path for an asset
+/// Referenced assets resolve to their external location
 #[command]
 pub async fn get_asset_path(
     app: tauri::AppHandle,
+    state: State<'_, AppState>,
     relative_path: String,
-) -> Result<String, String> {
-    let app_data_dir = app.path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+) -> Result<String, CommandError> {
+    let is_reference = sqlx::query(
+        "SELECT 1 FROM assets WHERE relative_path = ? AND import_mode = 'reference'",
+    )
+    .bind(&relative_path)
+    .fetch_optional(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to look up asset", e))?
+    .is_some();
+    if is_reference {
+        return Ok(relative_path);
+    }
     
-    let file_path = app_data_dir.join("assets").join(&relative_path);
+    let file_path = resolve_asset_path(&assets_root(&app, &state).await?, &relative_path)?;
     Ok(file_path.to_string_lossy().to_string())
 }
 
-/// Open the assets folder in the system file explorer
+/// Write a fresh thumbnail for an image in the assets folder, replacing any existing one
+/// Returns the thumbnail's relative path, or None when the image is missing or can't
+/// be decoded
 #[command]
-pub async fn open_assets_folder(app: tauri::AppHandle) -> Result<(), String> {
-    use std::process::Command;
+pub async fn regenerate_thumbnail(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    relative_path: String,
+) -> Result<Option<String>, CommandError> {
+    let assets_dir = assets_root(&app, &state).await?;
     
-    let app_data_dir = app.path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    if !resolve_asset_path(&assets_dir, &relative_path)?.is_file() {
+        log::warn!("Asset file not found: {}", relative_path);
+        return Ok(None);
+    }
+    
+    // Decoding large images is CPU heavy, keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || generate_thumbnail(&assets_dir, &relative_path))
+        .await
+        .map_err(|e| CommandError::internal("Failed to generate thumbnail", e))
+}
+
+/// Open an asset in the operating system's default application
+/// The path is resolved like `get_asset_path`, so the frontend never opens raw paths
+#[command]
+pub async fn open_asset_external(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    relative_path: String,
+) -> Result<(), CommandError> {
+    use tauri_plugin_shell::ShellExt;
+    
+    let file_path = get_asset_path(app.clone(), state, relative_path.clone()).await?;
+    if !std::path::Path::new(&file_path).is_file() {
+        return Err(CommandError::NotFound(format!("asset not found: {}", relative_path)));
+    }
+    
+    // The opener plugin would replace this, but the shell plugin is already set up;
+    // opening from Rust bypasses the frontend's URL scope
+    #[allow(deprecated)]
+    app.shell()
+        .open(file_path, None)
+        .map_err(|e| CommandError::internal("Failed to open asset", e))?;
+    
+    log::info!("Opened asset externally: {}", relative_path);
+    Ok(())
+}
+
+/// Page count, document info and size of a PDF asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfInfo {
+    pub page_count: u32,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub file_size: u64,
+}
+
+/// Read a text entry (`Title`, `Author`, ...) of the document info dictionary
+/// Missing, empty or undecodable entries are None
+fn pdf_info_text(document: &lopdf::Document, key: &[u8]) -> Option<String> {
+    let info = match document.trailer.get(b"Info").ok()? {
+        lopdf::Object::Reference(id) => document.get_dictionary(*id).ok()?,
+        lopdf::Object::Dictionary(info) => info,
+        _ => return None,
+    };
+    let text = lopdf::decode_text_string(info.get(key).ok()?).ok()?;
+    let text = text.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Get the page count, embedded title/author and file size of a PDF asset
+/// The path is resolved like `get_asset_path`. Files that aren't PDFs, can't be
+/// parsed or are encrypted are rejected with a validation error
+#[command]
+pub async fn get_pdf_info(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    relative_path: String,
+) -> Result<PdfInfo, CommandError> {
+    let file_path = std::path::PathBuf::from(get_asset_path(app, state, relative_path.clone()).await?);
+    let file_size = std::fs::metadata(&file_path)
+        .map_err(|_| CommandError::NotFound(format!("asset not found: {}", relative_path)))?
+        .len();
+    if infer::get_from_path(&file_path).ok().flatten().map(|kind| kind.mime_type()) != Some("application/pdf") {
+        return Err(CommandError::Validation(format!("not a PDF: {}", relative_path)));
+    }
+
+    // Parsing runs off the async runtime; a parser panic surfaces as a join error
+    let document = tauri::async_runtime::spawn_blocking(move || lopdf::Document::load(&file_path))
+        .await
+        .map_err(|e| CommandError::internal("Failed to read PDF", e))?
+        .map_err(|e| CommandError::Validation(format!("Failed to read PDF {}: {}", relative_path, e)))?;
+    if document.is_encrypted() {
+        return Err(CommandError::Validation(format!("PDF is encrypted: {}", relative_path)));
+    }
+
+    Ok(PdfInfo {
+        page_count: document.get_pages().len() as u32,
+        title: pdf_info_text(&document, b"Title"),
+        author: pdf_info_text(&document, b"Author"),
+        file_size,
+    })
+}
+
+/// Rename an asset within its folder and point card content/metadata at the new path
+/// The thumbnail of an image is renamed along with it. Returns the new relative path
+#[command]
+pub async fn rename_asset(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    relative_path: String,
+    new_filename: String,
+) -> Result<String, CommandError> {
+    use std::fs;
+    
+    let new_filename = new_filename.trim().to_string();
+    if new_filename.is_empty()
+        || new_filename == "."
+        || new_filename == ".."
+        || new_filename.contains(['/', '\\', '\0'])
+    {
+        return Err(CommandError::Validation(format!("Invalid file name: {}", new_filename)));
+    }
+    
+    let assets_dir = assets_root(&app, &state).await?;
+    
+    let old_path = resolve_asset_path(&assets_dir, &relative_path)?;
+    if !old_path.is_file() {
+        return Err(CommandError::NotFound(format!("asset not found: {}", relative_path)));
+    }
+    
+    let new_relative_path = match relative_path.rsplit_once('/') {
+        Some((subdir, _)) => format!("{}/{}", subdir, new_filename),
+        None => new_filename.clone(),
+    };
+    if new_relative_path == relative_path {
+        return Ok(relative_path);
+    }
+    let new_path = resolve_asset_path(&assets_dir, &new_relative_path)?;
+    if new_path.exists() {
+        return Err(CommandError::Conflict(format!("asset already exists: {}", new_relative_path)));
+    }
     
-    let assets_dir = app_data_dir.join("assets");
+    let old_thumbnail = thumbnail_relative_path(&relative_path);
+    let new_thumbnail = thumbnail_relative_path(&new_relative_path);
+    let old_thumbnail_path = resolve_asset_path(&assets_dir, &old_thumbnail)?;
+    let new_thumbnail_path = resolve_asset_path(&assets_dir, &new_thumbnail)?;
+    let has_thumbnail = old_thumbnail_path.is_file() && !new_thumbnail_path.exists();
+    
+    fs::rename(&old_path, &new_path)
+        .map_err(|e| CommandError::io("Failed to rename asset", e))?;
+    if has_thumbnail {
+        if let Err(e) = fs::rename(&old_thumbnail_path, &new_thumbnail_path) {
+            let _ = fs::rename(&new_path, &old_path);
+            return Err(CommandError::io("Failed to rename thumbnail", e));
+        }
+    }
+    
+    let result = rewrite_asset_references(
+        &state,
+        &[
+            (relative_path.clone(), new_relative_path.clone()),
+            (encoded_asset_path(&relative_path), encoded_asset_path(&new_relative_path)),
+            (old_thumbnail, new_thumbnail),
+        ],
+    )
+    .await;
+    
+    // Put the files back so the database and disk keep agreeing
+    if let Err(e) = result {
+        let _ = fs::rename(&new_path, &old_path);
+        if has_thumbnail {
+            let _ = fs::rename(&new_thumbnail_path, &old_thumbnail_path);
+        }
+        return Err(e);
+    }
+    
+    log::info!("Renamed asset {} to {}", relative_path, new_relative_path);
+    Ok(new_relative_path)
+}
+
+/// A relative path with its file name percent-encoded, as asset URLs embed it
+fn encoded_asset_path(relative_path: &str) -> String {
+    match relative_path.rsplit_once('/') {
+        Some((subdir, file_name)) => format!("{}/{}", subdir, percent_encode(file_name)),
+        None => percent_encode(relative_path),
+    }
+}
+
+/// Move the asset row (and its search entry) to a new path and rewrite card references
+/// `replacements` are (old, new) substrings; the first pair is the asset's own path
+async fn rewrite_asset_references(
+    state: &AppState,
+    replacements: &[(String, String)],
+) -> Result<(), CommandError> {
+    let (old_path, new_path) = &replacements[0];
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let mut tx = state
+        .writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+    
+    let asset: Option<(String, Option<String>)> = sqlx::query_as(
+        "UPDATE assets SET relative_path = ? WHERE relative_path = ? RETURNING id, extracted_text",
+    )
+    .bind(new_path)
+    .bind(old_path)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to update asset record", e))?;
+    if let Some((id, text)) = asset {
+        search::index_entity(&mut tx, "file", &id, asset_display_name(new_path), text.as_deref().unwrap_or_default(), "")
+            .await
+            .map_err(|e| CommandError::db("Failed to index file", e))?;
+    }
+    
+    replace_card_references(&mut tx, replacements, timestamp).await?;
+    
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))
+}
+
+/// Replace each (old, new) substring in card content and metadata
+/// Changed cards get their `asset_refs` recomputed
+async fn replace_card_references(
+    conn: &mut sqlx::SqliteConnection,
+    replacements: &[(String, String)],
+    timestamp: i64,
+) -> Result<(), CommandError> {
+    let mut changed = std::collections::BTreeSet::new();
+    for (old, new) in replacements {
+        if old == new {
+            continue;
+        }
+        let ids: Vec<String> = sqlx::query_scalar(
+            "UPDATE cards SET content = REPLACE(content, ?1, ?2),
+                              metadata = REPLACE(metadata, ?1, ?2),
+                              updated_at = ?3
+             WHERE instr(content, ?1) > 0 OR instr(metadata, ?1) > 0
+             RETURNING id",
+        )
+        .bind(old)
+        .bind(new)
+        .bind(timestamp)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to update asset references", e))?;
+        changed.extend(ids);
+    }
+    for card_id in &changed {
+        refresh_asset_refs(&mut *conn, card_id).await?;
+    }
+    Ok(())
+}
+
+#[derive(sqlx::FromRow)]
+struct RelinkedAsset {
+    relative_path: String,
+    file_type: String,
+    import_mode: String,
+}
+
+/// Whether a sniffed MIME type fits an asset's file type; "other" accepts anything
+fn matches_file_type(file_type: &str, mime_type: &str) -> bool {
+    match file_type {
+        "pdf" => mime_type == "application/pdf",
+        "image" => mime_type.starts_with("image/"),
+        _ => true,
+    }
+}
+
+/// Point an asset (typically one `verify_assets` reported missing) at a new file
+/// Referenced assets reference `new_source_path`; stored ones get a fresh copy in the
+/// assets folder, or reuse an identical file already in the library. Card content and
+/// metadata are rewritten to the new path, which is returned
+#[command]
+pub async fn relink_asset(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    asset_id: String,
+    new_source_path: String,
+) -> Result<String, CommandError> {
+    use std::fs;
+    use std::path::Path;
+    
+    let asset = sqlx::query_as::<_, RelinkedAsset>(
+        "SELECT relative_path, file_type, import_mode FROM assets WHERE id = ?",
+    )
+    .bind(&asset_id)
+    .fetch_optional(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get asset", e))?
+    .ok_or_else(|| CommandError::NotFound(format!("asset not found: {}", asset_id)))?;
+    
+    let source = Path::new(&new_source_path);
+    if !source.is_file() {
+        return Err(CommandError::NotFound(format!("Source file does not exist: {}", new_source_path)));
+    }
+    let source_info = read_asset_info(source, &new_source_path)?;
+    if !matches_file_type(&asset.file_type, &source_info.mime_type) {
+        return Err(CommandError::Validation(format!(
+            "{} is {}, which doesn't match the asset's file type ({})",
+            new_source_path, source_info.mime_type, asset.file_type
+        )));
+    }
+    
+    let assets_dir = assets_root(&app, &state).await?;
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let is_reference = asset.import_mode == "reference";
+    
+    let (new_relative_path, hash) = if is_reference {
+        let absolute_path = source.canonicalize()
+            .map_err(|e| CommandError::io("Failed to resolve source path", e))?;
+        (absolute_path.to_string_lossy().to_string(), None)
+    } else {
+        let hash = hash_file(source)?;
+        let subdir = match asset.file_type.as_str() {
+            "pdf" => "pdfs",
+            "image" => "images",
+            _ => "other",
+        };
+        let name = source.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+        let relative_path = format!("{}/{}_{}", subdir, timestamp, name);
+        (relative_path, Some(hash))
+    };
+    
+    // Another asset may already hold this content (or reference this file)
+    let existing: Option<String> = sqlx::query_scalar(
+        "SELECT relative_path FROM assets WHERE id != ? AND (hash = ? OR relative_path = ?)",
+    )
+    .bind(&asset_id)
+    .bind(&hash)
+    .bind(&new_relative_path)
+    .fetch_optional(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to look up asset", e))?;
+    let existing = match existing {
+        Some(existing) if is_reference => Some(existing),
+        Some(existing) if resolve_asset_path(&assets_dir, &existing)?.is_file() => Some(existing),
+        _ => None,
+    };
+    
+    // The file to index, unless the asset is folded into an existing one
+    let mut written = Vec::new();
+    let (new_relative_path, target_path) = match &existing {
+        Some(existing) => (existing.clone(), None),
+        None if is_reference => {
+            let target = std::path::PathBuf::from(&new_relative_path);
+            (new_relative_path, Some(target))
+        }
+        None => {
+            if let Some(parent) = resolve_asset_path(&assets_dir, &new_relative_path)?.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| CommandError::io("Failed to create assets directory", e))?;
+                ensure_disk_space(parent, source_info.file_size as u64)?;
+            }
+            let (new_relative_path, target) = create_asset_file(&assets_dir, &new_relative_path)?;
+            written.push(target.clone());
+            if let Err(e) = fs::copy(source, &target) {
+                let _ = fs::remove_file(&target);
+                return Err(CommandError::io("Failed to copy file", e));
+            }
+            if asset.file_type == "image" {
+                if let Some(thumbnail) = ensure_thumbnail(assets_dir.clone(), new_relative_path.clone()).await {
+                    written.push(assets_dir.join(thumbnail));
+                }
+            }
+            (new_relative_path, Some(target))
+        }
+    };
+    
+    let mut replacements = vec![
+        (asset.relative_path.clone(), new_relative_path.clone()),
+        (encoded_asset_path(&asset.relative_path), encoded_asset_path(&new_relative_path)),
+    ];
+    if !is_reference {
+        replacements.push((thumbnail_relative_path(&asset.relative_path), thumbnail_relative_path(&new_relative_path)));
+    }
+    
+    let result = async {
+        let mut tx = state.writer()
+            .begin()
+            .await
+            .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+        
+        // Folding into the existing asset drops this row; its search entry goes with it
+        if existing.is_some() {
+            sqlx::query("DELETE FROM assets WHERE id = ?")
+                .bind(&asset_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| CommandError::db("Failed to remove asset record", e))?;
+        } else {
+            sqlx::query(
+                "UPDATE assets SET relative_path = ?, hash = ?, file_size = ?, mime_type = ?, extracted_text = NULL
+                 WHERE id = ?",
+            )
+            .bind(&new_relative_path)
+            .bind(&hash)
+            .bind(source_info.file_size)
+            .bind(&source_info.mime_type)
+            .bind(&asset_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to update asset record", e))?;
+        }
+        replace_card_references(&mut tx, &replacements, timestamp).await?;
+        
+        tx.commit()
+            .await
+            .map_err(|e| CommandError::db("Failed to commit transaction", e))
+    }
+    .await;
+    if let Err(e) = result {
+        for path in &written {
+            let _ = fs::remove_file(path);
+        }
+        return Err(e);
+    }
+    
+    if let Some(target_path) = target_path {
+        index_asset(&state.writer(), &asset_id, &new_relative_path, target_path, &asset.file_type).await?;
+    }
+    
+    log::info!("Relinked asset {} from {} to {}", asset_id, asset.relative_path, new_relative_path);
+    Ok(new_relative_path)
+}
+
+/// An asset row whose file is missing on disk
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenAsset {
+    pub id: String,
+    pub relative_path: String,
+    pub import_mode: String,
+}
+
+/// Check every tracked asset and return the ones whose file no longer exists
+/// Referenced assets are checked at their external location
+#[command]
+pub async fn verify_assets(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<BrokenAsset>, CommandError> {
+    let assets_dir = assets_root(&app, &state).await?;
+    
+    let assets = sqlx::query_as::<_, BrokenAsset>(
+        "SELECT id, relative_path, import_mode FROM assets ORDER BY created_at ASC",
+    )
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get assets", e))?;
+    
+    let broken: Vec<BrokenAsset> = assets
+        .into_iter()
+        .filter(|asset| {
+            let exists = if asset.import_mode == "reference" {
+                std::path::Path::new(&asset.relative_path).is_file()
+            } else {
+                // A path that escapes the assets folder can't be served either
+                resolve_asset_path(&assets_dir, &asset.relative_path)
+                    .map(|path| path.is_file())
+                    .unwrap_or(false)
+            };
+            !exists
+        })
+        .collect();
+    
+    if !broken.is_empty() {
+        log::warn!("Found {} missing asset files", broken.len());
+    }
+    Ok(broken)
+}
+
+/// Disk usage of one folder inside the assets directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetsDirUsage {
+    pub name: String,
+    pub total_bytes: u64,
+    pub file_count: u64,
+}
+
+/// Disk usage of the whole assets directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetsUsage {
+    pub total_bytes: u64,
+    pub file_count: u64,
+    /// pdfs, images, other and thumbnails first, then any unexpected folders
+    pub subdirs: Vec<AssetsDirUsage>,
+    /// Files with a row in the assets table
+    pub tracked_files: u64,
+    /// Files with no row in the assets table (thumbnails aren't counted)
+    pub loose_files: u64,
+}
+
+/// Walk the assets directory and report its size, per folder and in total
+/// Files directly in the assets folder are grouped under "."
+#[command]
+pub async fn get_assets_usage(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AssetsUsage, CommandError> {
+    use std::collections::HashSet;
+    
+    let assets_dir = assets_root(&app, &state).await?;
+    
+    let mut files = Vec::new();
+    if assets_dir.exists() {
+        collect_asset_files(&assets_dir, "", &mut files)
+            .map_err(|e| CommandError::io("Failed to list assets", e))?;
+    }
+    
+    let tracked: HashSet<String> = sqlx::query_scalar::<_, String>(
+        "SELECT relative_path FROM assets WHERE import_mode != 'reference'",
+    )
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get assets", e))?
+    .into_iter()
+    .map(|path| path.replace('\\', "/"))
+    .collect();
+    
+    let mut usage = AssetsUsage {
+        total_bytes: 0,
+        file_count: 0,
+        subdirs: ["pdfs", "images", "other", "thumbnails"]
+            .iter()
+            .map(|name| AssetsDirUsage { name: name.to_string(), total_bytes: 0, file_count: 0 })
+            .collect(),
+        tracked_files: 0,
+        loose_files: 0,
+    };
+    
+    for relative_path in files {
+        let size = std::fs::metadata(assets_dir.join(&relative_path))
+            .map_err(|e| CommandError::io(&format!("Failed to read asset {}", relative_path), e))?
+            .len();
+        let name = match relative_path.split_once('/') {
+            Some((dir, _)) => dir,
+            None => ".",
+        };
+        
+        let index = match usage.subdirs.iter().position(|dir| dir.name == name) {
+            Some(index) => index,
+            None => {
+                usage.subdirs.push(AssetsDirUsage { name: name.to_string(), total_bytes: 0, file_count: 0 });
+                usage.subdirs.len() - 1
+            }
+        };
+        usage.subdirs[index].total_bytes += size;
+        usage.subdirs[index].file_count += 1;
+        usage.total_bytes += size;
+        usage.file_count += 1;
+        
+        if tracked.contains(&relative_path) {
+            usage.tracked_files += 1;
+        } else if name != "thumbnails" {
+            usage.loose_files += 1;
+        }
+    }
+    
+    Ok(usage)
+}
+
+// ============================================
+// Asset Garbage Collection
+// ============================================
+
+/// Text columns that may embed asset paths
+const ASSET_REFERENCE_SOURCES: &[&str] = &[
+    "SELECT content FROM cards",
+    "SELECT metadata FROM cards",
+    "SELECT thumbnail_path FROM projects",
+    "SELECT settings FROM projects",
+    "SELECT metadata FROM canvas_elements",
+    "SELECT file_path FROM files",
+    "SELECT thumbnail_path FROM files",
+    "SELECT metadata FROM files",
+    "SELECT content FROM highlights",
+    "SELECT content FROM journal_entries",
+    "SELECT content_snapshot FROM version_history",
+];
+
+/// Relative paths (with `/` separators) of every file below `dir`
+pub(crate) fn collect_asset_files(dir: &std::path::Path, prefix: &str, out: &mut Vec<String>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let relative_path = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+        if entry.file_type()?.is_dir() {
+            collect_asset_files(&entry.path(), &relative_path, out)?;
+        } else {
+            out.push(relative_path);
+        }
+    }
+    Ok(())
+}
+
+/// Percent-encode a file name the way asset URLs embed it
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Find asset files that nothing in the database refers to, and delete them unless `dry_run`
+/// Card content/metadata (trashed cards included), board snapshots and the other
+/// entity tables are searched for each file name, raw or percent-encoded.
+/// Thumbnails are kept as long as their image is. Returns the unused relative paths
+#[command]
+pub async fn gc_unused_assets(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    dry_run: bool,
+) -> Result<Vec<String>, CommandError> {
+    use std::collections::HashSet;
+    
+    let assets_dir = assets_root(&app, &state).await?;
+    if !assets_dir.exists() {
+        return Ok(Vec::new());
+    }
+    
+    // An image may so far only be referenced by a snapshot still in the autosave buffer
+    super::boards::write_pending_snapshots(&state, None).await?;
+    
+    let mut files = Vec::new();
+    collect_asset_files(&assets_dir, "", &mut files)
+        .map_err(|e| CommandError::io("Failed to list assets", e))?;
+    
+    // Everything that could mention an asset, as one searchable blob
+    let mut corpus = String::new();
+    for sql in ASSET_REFERENCE_SOURCES {
+        let values: Vec<Option<String>> = sqlx::query_scalar(sql)
+            .fetch_all(&state.pool())
+            .await
+            .map_err(|e| CommandError::db("Failed to scan asset references", e))?;
+        for value in values.into_iter().flatten() {
+            corpus.push_str(&value);
+            corpus.push('\n');
+        }
+    }
+    let snapshots: Vec<Option<Vec<u8>>> = sqlx::query_scalar("SELECT tldraw_snapshot FROM boards")
+        .fetch_all(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to scan asset references", e))?;
+    for snapshot in snapshots.into_iter().flatten() {
+        corpus.push_str(&super::boards::decompress_snapshot(&snapshot)?);
+        corpus.push('\n');
+    }
+    
+    let is_referenced = |file_name: &str| {
+        corpus.contains(file_name) || corpus.contains(&percent_encode(file_name))
+    };
+    let file_name_of = |relative_path: &str| {
+        relative_path.rsplit('/').next().unwrap_or(relative_path).to_string()
+    };
+    
+    let (thumbnails, sources): (Vec<String>, Vec<String>) =
+        files.into_iter().partition(|path| path.starts_with("thumbnails/"));
+    let (mut unused, kept): (Vec<String>, Vec<String>) =
+        sources.into_iter().partition(|path| !is_referenced(&file_name_of(path)));
+    
+    // A thumbnail is named after its image plus ".png"
+    let kept_images: HashSet<String> = kept.iter().map(|path| file_name_of(path)).collect();
+    unused.extend(thumbnails.into_iter().filter(|path| {
+        let image_name = file_name_of(path);
+        let image_name = image_name.strip_suffix(".png").unwrap_or(&image_name);
+        !kept_images.contains(image_name) && !is_referenced(&file_name_of(path))
+    }));
+    unused.sort();
+    
+    if dry_run {
+        log::info!("Found {} unused assets (dry run)", unused.len());
+        return Ok(unused);
+    }
+    
+    for relative_path in &unused {
+        let file_path = resolve_asset_path(&assets_dir, relative_path)?;
+        std::fs::remove_file(&file_path)
+            .map_err(|e| CommandError::io(&format!("Failed to delete asset {}", relative_path), e))?;
+        sqlx::query("DELETE FROM assets WHERE relative_path = ?")
+            .bind(relative_path)
+            .execute(&state.writer())
+            .await
+            .map_err(|e| CommandError::db("Failed to remove asset record", e))?;
+    }
+    
+    log::info!("Deleted {} unused assets", unused.len());
+    Ok(unused)
+}
+
+// ============================================
+// Asset References
+// ============================================
+
+/// Recompute which assets a card embeds, on the caller's transaction
+/// An asset counts when its full relative path (absolute for references) appears in the
+/// card's content or metadata, raw or percent-encoded as in asset URLs. Candidate paths
+/// are picked out of the text and looked up by key, so saving a card doesn't scan the
+/// assets table. Trashed cards keep their references
+pub(crate) async fn refresh_asset_refs(conn: &mut sqlx::SqliteConnection, card_id: &str) -> Result<(), CommandError> {
+    use std::collections::BTreeSet;
+    
+    sqlx::query("DELETE FROM asset_refs WHERE card_id = ?")
+        .bind(card_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to update asset references", e))?;
+    
+    let card: Option<(Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT content, metadata FROM cards WHERE id = ?")
+            .bind(card_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| CommandError::db("Failed to get card", e))?;
+    let Some((content, metadata)) = card else {
+        return Ok(());
+    };
+    let text: String = [content, metadata].into_iter().flatten().collect::<Vec<_>>().join("\n");
+    if text.is_empty() {
+        return Ok(());
+    }
+    let decoded = unescape_embedded_paths(&text);
+    
+    let mut prefixes = BTreeSet::new();
+    for haystack in [text.as_str(), decoded.as_str()] {
+        prefixes.extend(copied_asset_prefixes(haystack));
+        prefixes.extend(absolute_path_prefixes(haystack));
+    }
+    
+    let mut paths = BTreeSet::new();
+    for prefix in prefixes {
+        // Every asset whose path starts with `prefix`, as an index range
+        let found: Vec<String> = sqlx::query_scalar(
+            "SELECT relative_path FROM assets WHERE relative_path >= ? AND relative_path < ?"
+        )
+            .bind(prefix)
+            .bind(format!("{}\u{10FFFF}", prefix))
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| CommandError::db("Failed to get assets", e))?;
+        paths.extend(found.into_iter().filter(|path| text.contains(path.as_str()) || decoded.contains(path.as_str())));
+    }
+    
+    for relative_path in paths {
+        sqlx::query("INSERT INTO asset_refs (card_id, relative_path) VALUES (?, ?)")
+            .bind(card_id)
+            .bind(&relative_path)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| CommandError::db("Failed to update asset references", e))?;
+    }
+    Ok(())
+}
+
+/// `text` with `%XX` escapes decoded and JSON-escaped slashes and backslashes undone,
+/// so paths inside asset URLs and JSON strings read as they are stored
+fn unescape_embedded_paths(text: &str) -> String {
+    let text = text.replace("\\/", "/").replace("\\\\", "\\");
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(b) => {
+                out.push(b);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Every `subdir/timestamp_` or `subdir/timestamp-n_` in `text`: the part of a copied
+/// asset's path that `create_asset_file` fixes before the file name
+fn copied_asset_prefixes(text: &str) -> Vec<&str> {
+    let bytes = text.as_bytes();
+    let is_subdir_byte = |b: u8| b.is_ascii_alphanumeric() || b == b'-' || b == b'_';
+    let digits_from = |mut i: usize| {
+        let start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        (i > start).then_some(i)
+    };
+    
+    let mut prefixes = Vec::new();
+    for (slash, _) in text.match_indices('/') {
+        let mut start = slash;
+        while start > 0 && is_subdir_byte(bytes[start - 1]) {
+            start -= 1;
+        }
+        let Some(mut end) = digits_from(slash + 1) else { continue };
+        if start == slash {
+            continue;
+        }
+        if bytes.get(end) == Some(&b'-') {
+            match digits_from(end + 1) {
+                Some(after) => end = after,
+                None => continue,
+            }
+        }
+        if bytes.get(end) == Some(&b'_') {
+            prefixes.push(&text[start..=end]);
+        }
+    }
+    prefixes
+}
+
+/// Text from each place an absolute path (`/...`, `C:\...`) starts up to the next
+/// delimiter, as used for referenced assets. A path with spaces or brackets in it is
+/// only matched up to the first one, so the lookup is by prefix
+fn absolute_path_prefixes(text: &str) -> Vec<&str> {
+    const BOUNDARY: &[char] = &['"', '\'', '(', ')', '<', '>', '=', '[', ']', ' ', '\t', '\n', '\r'];
+    const END: &[char] = &['"', '\'', '(', ')', '<', '>', '[', ']', '?', '#', ' ', '\t', '\n', '\r'];
+    
+    let bytes = text.as_bytes();
+    let mut prefixes = Vec::new();
+    for (start, c) in text.char_indices() {
+        let unix = c == '/';
+        let windows = c.is_ascii_alphabetic()
+            && bytes.get(start + 1) == Some(&b':')
+            && matches!(bytes.get(start + 2), Some(b'\\') | Some(b'/'));
+        if !unix && !windows {
+            continue;
+        }
+        let before = &text[..start];
+        let at_boundary = before.is_empty()
+            || before.ends_with(BOUNDARY)
+            || before.ends_with("localhost/");
+        if !at_boundary {
+            continue;
+        }
+        let rest = &text[start..];
+        let prefix = &rest[..rest.find(END).unwrap_or(rest.len())];
+        // Leaves out lone slashes and `//` in ordinary text
+        if prefix.trim_start_matches('/').len() >= 2 {
+            prefixes.push(prefix);
+        }
+    }
+    prefixes
+}
+
+/// Number of cards, trashed ones included, that embed the asset at `relative_path`
+async fn asset_ref_count(conn: &mut sqlx::SqliteConnection, relative_path: &str) -> Result<i64, CommandError> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM asset_refs WHERE relative_path = ?")
+        .bind(relative_path)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to count asset references", e))
+}
+
+/// Get how many cards embed the asset at `relative_path`
+/// Trashed cards count until they are purged; 0 for files the assets table doesn't know
+#[command]
+pub async fn get_asset_ref_count(state: State<'_, AppState>, relative_path: String) -> Result<i64, CommandError> {
+    let mut conn = state.pool()
+        .acquire()
+        .await
+        .map_err(|e| CommandError::db("Failed to get a connection", e))?;
+    asset_ref_count(&mut conn, &relative_path).await
+}
+
+/// Get the cards that embed the asset at `relative_path`, most recently updated first
+/// Trashed cards are included (their `deletedAt` is set) since they still keep the
+/// file from being deleted. Empty when nothing uses it
+#[command]
+pub async fn get_cards_referencing_asset(
+    state: State<'_, AppState>,
+    relative_path: String,
+) -> Result<Vec<Card>, CommandError> {
+    sqlx::query_as::<_, Card>(&format!(
+        "SELECT {} FROM asset_refs r JOIN cards c ON c.id = r.card_id
+         WHERE r.relative_path = ?
+         ORDER BY c.updated_at DESC",
+        card_columns("c")
+    ))
+    .bind(&relative_path)
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get cards using asset", e))
+}
+
+#[command]
+pub async fn open_assets_folder(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), CommandError> {
+    use std::process::Command;
+    
+    let assets_dir = assets_root(&app, &state).await?;
     
     #[cfg(target_os = "windows")]
     {
         Command::new("explorer")
             .arg(&assets_dir)
             .spawn()
-            .map_err(|e| format!("Failed to open folder: {}", e))?;
+            .map_err(|e| CommandError::io("Failed to open folder", e))?;
     }
     
     #[cfg(target_os = "macos")]
@@ -168,7 +1854,7 @@ pub async fn open_assets_folder(app: tauri::AppHandle) -> Result<(), String> {
         Command::new("open")
             .arg(&assets_dir)
             .spawn()
-            .map_err(|e| format!("Failed to open folder: {}", e))?;
+            .map_err(|e| CommandError::io("Failed to open folder", e))?;
     }
     
     #[cfg(target_os = "linux")]
@@ -176,45 +1862,77 @@ pub async fn open_assets_folder(app: tauri::AppHandle) -> Result<(), String> {
         Command::new("xdg-open")
             .arg(&assets_dir)
             .spawn()
-            .map_err(|e| format!("Failed to open folder: {}", e))?;
+            .map_err(|e| CommandError::io("Failed to open folder", e))?;
     }
     
     Ok(())
 }
 
 /// Save binary data (as base64) to the assets folder
-/// This is used for files from clipboard/paste that don't have a filesystem path
+/// This is used for files from clipboard/paste that don't have a filesystem path.
+/// The data is written to `assets/temp` and renamed into place, so a crash never
+/// leaves a half-written asset. Content that is already in the library is reused
 #[command]
 pub async fn save_bytes_to_assets(
     app: tauri::AppHandle,
+    state: State<'_, AppState>,
     data: String,        // Base64-encoded data
     filename: String,    // Original filename 
     file_type: String,   // "pdf" | "image"
-) -> Result<String, String> {
-    use std::fs;
+) -> Result<String, CommandError> {
     use base64::{Engine as _, engine::general_purpose::STANDARD};
     
     // Decode base64 data
     let bytes = STANDARD.decode(&data)
-        .map_err(|e| format!("Failed to decode base64: {}", e))?;
+        .map_err(|e| CommandError::Validation(format!("Failed to decode base64: {}", e)))?;
     
-    // Get app data directory
-    let app_data_dir = app.path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    save_asset_bytes(&app, &state, &bytes, &filename, &file_type).await
+}
+
+/// Write `bytes` into the assets folder as `save_bytes_to_assets` does
+async fn save_asset_bytes(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    bytes: &[u8],
+    filename: &str,
+    file_type: &str,
+) -> Result<String, CommandError> {
+    use std::fs;
+    use std::io::Write;
+    use sha2::{Digest, Sha256};
+    
+    let assets_root = assets_root(app, state).await?;
+    
+    // Reuse the existing asset if this content was saved before
+    let hash = format!("{:x}", Sha256::digest(bytes));
+    let existing: Option<String> = sqlx::query_scalar("SELECT relative_path FROM assets WHERE hash = ?")
+        .bind(&hash)
+        .fetch_optional(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to look up asset", e))?;
+    if let Some(existing) = existing {
+        if resolve_asset_path(&assets_root, &existing)?.exists() {
+            log::info!("Reusing existing asset: {}", existing);
+            return Ok(existing);
+        }
+    }
     
     // Determine target subdirectory
-    let subdir = match file_type.as_str() {
+    let subdir = match file_type {
         "pdf" => "pdfs",
         "image" => "images",
         _ => "other",
     };
     
-    let assets_dir = app_data_dir.join("assets").join(subdir);
+    let assets_dir = resolve_asset_path(&assets_root, subdir)?;
+    let temp_dir = resolve_asset_path(&assets_root, "temp")?;
     
-    // Create directory if it doesn't exist
-    fs::create_dir_all(&assets_dir)
-        .map_err(|e| format!("Failed to create assets directory: {}", e))?;
+    // Create directories if they don't exist
+    for dir in [&assets_dir, &temp_dir] {
+        fs::create_dir_all(dir)
+            .map_err(|e| CommandError::io("Failed to create assets directory", e))?;
+    }
+    ensure_disk_space(&assets_dir, bytes.len() as u64)?;
     
     // Generate unique filename: timestamp_originalname
     let timestamp = chrono::Utc::now().timestamp_millis();
@@ -223,17 +1941,228 @@ pub async fn save_bytes_to_assets(
         .filter(|c| c.is_alphanumeric() || *c == '.' || *c == '-' || *c == '_')
         .collect();
     let safe_filename = if safe_filename.is_empty() { "file".to_string() } else { safe_filename };
-    let new_filename = format!("{}_{}", timestamp, safe_filename);
+    let (relative_path, target_path) =
+        create_asset_file(&assets_root, &format!("{}/{}_{}", subdir, timestamp, safe_filename))?;
+    let temp_path = temp_dir.join(format!("{}.tmp", uuid::Uuid::new_v4()));
     
-    let target_path = assets_dir.join(&new_filename);
+    // Write and flush the temp file, then move it into place in one step
+    let written = fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(bytes)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, &target_path));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp_path);
+        let _ = fs::remove_file(&target_path);
+        return Err(CommandError::io("Failed to write file", e));
+    }
     
-    // Write the bytes to file
-    fs::write(&target_path, bytes)
-        .map_err(|e| format!("Failed to write file: {}", e))?;
+    let info = read_asset_info(&target_path, &relative_path)?;
+    if file_type == "image" {
+        ensure_thumbnail(assets_root, relative_path.clone()).await;
+    }
     
-    // Return the relative path from assets folder
-    let relative_path = format!("{}/{}", subdir, new_filename);
-    log::info!("Saved bytes to assets: {} ({} bytes from base64)", relative_path, data.len());
+    // Track the hash; a stale row whose file went missing now points at the fresh copy
+    let asset_id: String = sqlx::query_scalar(
+        "INSERT INTO assets (id, hash, relative_path, file_type, file_size, mime_type, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(hash) DO UPDATE SET relative_path = excluded.relative_path
+         RETURNING id",
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(&hash)
+    .bind(&relative_path)
+    .bind(file_type)
+    .bind(info.file_size)
+    .bind(&info.mime_type)
+    .bind(timestamp)
+    .fetch_one(&state.writer())
+    .await
+    .map_err(|e| CommandError::db("Failed to record asset", e))?;
+    index_asset(&state.writer(), &asset_id, &relative_path, target_path, file_type).await?;
+    
+    log::info!("Saved bytes to assets: {} ({} bytes)", relative_path, bytes.len());
     
     Ok(relative_path)
 }
+
+/// Save the image on the clipboard as a PNG asset and return its relative path
+/// Returns None when the clipboard holds no image
+#[command]
+pub async fn paste_image_from_clipboard(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, CommandError> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+    
+    // Reading the clipboard can block (and deadlock on the main thread on Linux)
+    let clipboard_app = app.clone();
+    let png = tauri::async_runtime::spawn_blocking(move || {
+        let image = match clipboard_app.clipboard().read_image() {
+            Ok(image) => image,
+            Err(e) => {
+                log::info!("No image on clipboard: {}", e);
+                return Ok(None);
+            }
+        };
+        
+        let buffer = image::RgbaImage::from_raw(image.width(), image.height(), image.rgba().to_vec())
+            .ok_or_else(|| CommandError::Internal("Clipboard image has an invalid size".to_string()))?;
+        let mut png = std::io::Cursor::new(Vec::new());
+        buffer
+            .write_to(&mut png, image::ImageFormat::Png)
+            .map_err(|e| CommandError::internal("Failed to encode clipboard image", e))?;
+        Ok(Some(png.into_inner()))
+    })
+    .await
+    .map_err(|e| CommandError::internal("Failed to read clipboard", e))??;
+    
+    match png {
+        Some(png) => save_asset_bytes(&app, &state, &png, "clipboard.png", "image").await.map(Some),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::cards::update_card;
+    use crate::test_support::{seed_card, test_app};
+
+    #[test]
+    fn resolve_asset_path_accepts_plain_relative_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let assets = dir.path().join("assets");
+
+        let path = resolve_asset_path(&assets, "images/a.png").unwrap();
+        assert_eq!(path, assets.join("images").join("a.png"));
+    }
+
+    #[test]
+    fn resolve_asset_path_rejects_parent_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let assets = dir.path().join("assets");
+
+        assert!(resolve_asset_path(&assets, "../x").is_err());
+        assert!(resolve_asset_path(&assets, "images/../../x").is_err());
+        assert!(resolve_asset_path(&assets, "").is_err());
+    }
+
+    #[test]
+    fn resolve_asset_path_rejects_absolute_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let assets = dir.path().join("assets");
+
+        assert!(resolve_asset_path(&assets, "/etc/passwd").is_err());
+        #[cfg(windows)]
+        {
+            assert!(resolve_asset_path(&assets, "C:\\Windows\\win.ini").is_err());
+            assert!(resolve_asset_path(&assets, "\\\\server\\share\\x").is_err());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_asset_path_rejects_symlinks_out_of_assets() {
+        let dir = tempfile::tempdir().unwrap();
+        let assets = dir.path().join("assets");
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(&assets).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("secret.txt"), b"secret").unwrap();
+        std::os::unix::fs::symlink(&outside, assets.join("images")).unwrap();
+
+        assert!(resolve_asset_path(&assets, "images/secret.txt").is_err());
+        assert!(resolve_asset_path(&assets, "images/new.png").is_err());
+    }
+
+    #[test]
+    fn create_asset_file_numbers_same_name_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("pdfs")).unwrap();
+
+        // Two imports of "a.pdf" within the same millisecond
+        let (first, first_path) = create_asset_file(dir.path(), "pdfs/1700000000000_a.pdf").unwrap();
+        std::fs::write(&first_path, b"first import").unwrap();
+        let (second, second_path) = create_asset_file(dir.path(), "pdfs/1700000000000_a.pdf").unwrap();
+        std::fs::write(&second_path, b"second import").unwrap();
+
+        assert_eq!(first, "pdfs/1700000000000_a.pdf");
+        assert_eq!(second, "pdfs/1700000000000-1_a.pdf");
+        assert_eq!(std::fs::read(&first_path).unwrap(), b"first import");
+        assert_eq!(std::fs::read(&second_path).unwrap(), b"second import");
+        assert_eq!(asset_display_name(&second), "a.pdf");
+    }
+
+    #[test]
+    fn create_asset_file_claims_distinct_names_across_threads() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("images")).unwrap();
+
+        let claimed: Vec<String> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..16)
+                .map(|_| scope.spawn(|| create_asset_file(dir.path(), "images/1700000000000_a.png").unwrap().0))
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        let unique: std::collections::HashSet<&String> = claimed.iter().collect();
+        assert_eq!(unique.len(), claimed.len());
+    }
+
+    #[test]
+    fn copied_asset_prefixes_stop_before_the_file_name() {
+        let text = r#"{"src":"images/1700000000000_a.png","alt":"pdfs/1700000000000-2_b c.pdf", 12/34 x/5y_"}"#;
+        assert_eq!(
+            copied_asset_prefixes(text),
+            vec!["images/1700000000000_", "pdfs/1700000000000-2_"]
+        );
+    }
+
+    #[tokio::test]
+    async fn asset_refs_match_full_paths_only() {
+        let (app, _dir) = test_app().await;
+        seed_card(&app, "c").await;
+        let state = app.state::<AppState>();
+        for (relative_path, import_mode) in [
+            ("images/1700000000000_data.png", "copy"),
+            ("images/1700000000001_a.png", "copy"),
+            ("images/1700000000002_my photo.png", "copy"),
+            ("/home/me/Papers/My Paper.pdf", "reference"),
+            ("/home/me/Papers/Other.pdf", "reference"),
+            ("/home/me/a.png", "reference"),
+        ] {
+            sqlx::query(
+                "INSERT INTO assets (id, relative_path, file_type, import_mode, created_at) VALUES (?, ?, 'image', ?, 0)"
+            )
+                .bind(relative_path)
+                .bind(relative_path)
+                .bind(import_mode)
+                .execute(&state.writer())
+                .await
+                .unwrap();
+        }
+
+        // `a.png` is inside `data.png`, but neither of the two `a.png` assets is embedded
+        let content = concat!(
+            r#"{"type":"doc","content":["#,
+            r#"{"type":"image","attrs":{"src":"images/1700000000000_data.png","alt":"a.png"}},"#,
+            r#"{"type":"image","attrs":{"src":"asset://localhost/%2Fdata%2Fassets%2Fimages%2F1700000000002_my%20photo.png"}},"#,
+            r#"{"type":"pdf","attrs":{"relativePath":"/home/me/Papers/My Paper.pdf"}}"#,
+            r#"]}"#,
+        );
+        update_card(app.state(), "c".into(), None, Some(content.into()), None, None, None)
+            .await
+            .unwrap();
+
+        let refs: Vec<String> = sqlx::query_scalar("SELECT relative_path FROM asset_refs WHERE card_id = 'c' ORDER BY relative_path")
+            .fetch_all(&state.pool())
+            .await
+            .unwrap();
+        assert_eq!(
+            refs,
+            vec!["/home/me/Papers/My Paper.pdf", "images/1700000000000_data.png", "images/1700000000002_my photo.png"]
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use tauri::{command, State};
+
+use super::app_state::assets_root;
+use super::cards::{Card, CARD_COLUMNS};
+use super::content::{escape_html, markdown_to_tiptap, tiptap_to_html};
+use super::markdown::local_image_path;
+use crate::db::AppState;
+use crate::error::CommandError;
+
+// ============================================
+// HTML Export
+// ============================================
+
+const PAGE_STYLE: &str = "body { font-family: system-ui, sans-serif; max-width: 48rem; margin: 2rem auto; padding: 0 1rem; line-height: 1.5; color: #222; }
+article { border-left: 4px solid #ddd; padding: 0.25rem 1rem; margin: 1.5rem 0; }
+img { max-width: 100%; }
+pre { background: #f5f5f5; padding: 0.75rem; overflow-x: auto; white-space: pre-wrap; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ddd; padding: 0.25rem 0.5rem; }
+ul.tasks { list-style: none; padding-left: 1rem; }";
+
+/// A local image as a `data:` URL so the page needs no other files
+fn image_data_url(path: &Path) -> Result<String, CommandError> {
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+    let bytes = std::fs::read(path)
+        .map_err(|e| CommandError::io(&format!("Failed to read image {}", path.display()), e))?;
+    let mime_type = infer::get(&bytes)
+        .map(|kind| kind.mime_type())
+        .unwrap_or("application/octet-stream");
+    Ok(format!("data:{};base64,{}", mime_type, STANDARD.encode(&bytes)))
+}
+
+/// A card as an `<article>`; markdown cards are converted like imported notes
+fn card_html(card: &Card, resolve_image: &mut dyn FnMut(&str) -> String) -> String {
+    let body = match card.content_type.as_str() {
+        "markdown" => tiptap_to_html(&markdown_to_tiptap(&card.content).to_string(), resolve_image),
+        "code" => format!("<pre><code>{}</code></pre>", escape_html(&card.content)),
+        _ => tiptap_to_html(&card.content, resolve_image),
+    };
+    let style = card
+        .color
+        .as_deref()
+        .map(|color| format!(" style=\"border-color: {}\"", escape_html(color)))
+        .unwrap_or_default();
+    let title = card
+        .title
+        .as_deref()
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .map(|title| format!("<h2>{}</h2>\n", escape_html(title)))
+        .unwrap_or_default();
+    format!("<article{}>\n{}{}\n</article>", style, title, body)
+}
+
+/// Export a board as one self-contained HTML page at `out_path` and return the path
+/// The board title is the heading and cards follow in canvas order (top to bottom,
+/// then left to right). Local images are embedded as data URLs; remote ones are
+/// linked as they are. Trashed cards are left out
+#[command]
+pub async fn export_board_html(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    board_id: String,
+    out_path: String,
+) -> Result<String, CommandError> {
+    let title: String = sqlx::query_scalar("SELECT title FROM boards WHERE id = ?")
+        .bind(&board_id)
+        .fetch_optional(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to get board", e))?
+        .ok_or_else(|| CommandError::NotFound(format!("board not found: {}", board_id)))?;
+
+    let cards = sqlx::query_as::<_, Card>(&format!(
+        "SELECT {} FROM cards
+         JOIN (SELECT card_id, x, y FROM board_cards WHERE board_id = ?) ON card_id = id
+         WHERE deleted_at IS NULL
+         ORDER BY y, x",
+        CARD_COLUMNS
+    ))
+    .bind(&board_id)
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get cards", e))?;
+
+    let assets_dir = assets_root(&app, &state).await?;
+
+    let mut embedded: HashMap<PathBuf, String> = HashMap::new();
+    let mut embed_error = None;
+    let mut resolve_image = |src: &str| {
+        let Some(source) = local_image_path(src, &assets_dir) else {
+            return src.to_string();
+        };
+        if let Some(url) = embedded.get(&source) {
+            return url.clone();
+        }
+        match image_data_url(&source) {
+            Ok(url) => {
+                embedded.insert(source, url.clone());
+                url
+            }
+            Err(e) => {
+                embed_error.get_or_insert(e);
+                src.to_string()
+            }
+        }
+    };
+
+    let articles: Vec<String> = cards.iter().map(|card| card_html(card, &mut resolve_image)).collect();
+    if let Some(e) = embed_error {
+        return Err(e);
+    }
+
+    let title = escape_html(&title);
+    let page = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{0}</title>\n<style>\n{1}\n</style>\n</head>\n<body>\n<h1>{0}</h1>\n{2}\n</body>\n</html>\n",
+        title,
+        PAGE_STYLE,
+        articles.join("\n")
+    );
+
+    let out_path = PathBuf::from(out_path);
+    if let Some(parent) = out_path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| CommandError::io("Failed to create export folder", e))?;
+    }
+    std::fs::write(&out_path, page)
+        .map_err(|e| CommandError::io(&format!("Failed to write {}", out_path.display()), e))?;
+
+    log::info!("Exported board {} ({} cards) as HTML to {:?}", board_id, cards.len(), out_path);
+    Ok(out_path.to_string_lossy().to_string())
+}
@@ -0,0 +1,379 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{command, State};
+
+use super::app_state::assets_root;
+use super::cards::{insert_card_row, Card, NewCard, CARD_COLUMNS};
+use super::content::{markdown_to_tiptap, tiptap_to_markdown, tiptap_to_plaintext};
+use super::database::{import_asset_copy, resolve_asset_path};
+use super::search::{self, IndexEntity};
+use crate::db::AppState;
+use crate::error::CommandError;
+
+// ============================================
+// Markdown Commands
+// ============================================
+
+/// Longest file name stem (in characters) used for exported notes
+const MAX_FILENAME_LEN: usize = 100;
+
+/// Decode `%XX` escapes, leaving malformed ones as they are
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Local file behind an image `src`: an asset URL (`asset://` or `http://asset.localhost`),
+/// a path relative to the assets folder or an absolute path. Remote and data URLs return None
+pub(crate) fn local_image_path(src: &str, assets_dir: &Path) -> Option<PathBuf> {
+    if src.is_empty() || src.starts_with("data:") {
+        return None;
+    }
+
+    let decoded = percent_decode(src).replace('\\', "/");
+    let decoded = decoded.split(['?', '#']).next().unwrap_or_default();
+    let relative = decoded.rsplit_once("/assets/").map(|(_, rest)| rest).unwrap_or(decoded);
+    if let Some(path) = resolve_asset_path(assets_dir, relative).ok().filter(|p| p.is_file()) {
+        return Some(path);
+    }
+
+    let absolute = PathBuf::from(percent_decode(src));
+    Some(absolute).filter(|p| p.is_absolute() && p.is_file())
+}
+
+/// File name stem for a card: its title with unsafe characters replaced, or the id
+fn note_filename(card: &Card) -> String {
+    let title = card.title.as_deref().unwrap_or_default();
+    let safe: String = title
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .take(MAX_FILENAME_LEN)
+        .collect();
+    let safe = safe.trim().trim_matches('.').trim();
+    if safe.is_empty() {
+        card.id.clone()
+    } else {
+        safe.to_string()
+    }
+}
+
+/// `name`, or `name (2)`, `name (3)`... if it was already used (case-insensitively)
+fn unique_name(name: &str, extension: &str, used: &mut HashSet<String>) -> String {
+    let mut candidate = format!("{}.{}", name, extension);
+    let mut counter = 2;
+    while !used.insert(candidate.to_lowercase()) {
+        candidate = format!("{} ({}).{}", name, counter, extension);
+        counter += 1;
+    }
+    candidate
+}
+
+/// Export cards as Markdown files in `out_dir`, one per card, and return the written paths
+/// Files are named after the card title (or id); images are copied to `out_dir/assets`
+#[command]
+pub async fn export_cards_markdown(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    card_ids: Vec<String>,
+    out_dir: String,
+) -> Result<Vec<String>, CommandError> {
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new(format!(
+        "SELECT {} FROM cards WHERE id IN (",
+        CARD_COLUMNS
+    ));
+    let mut ids = query.separated(", ");
+    for id in &card_ids {
+        ids.push_bind(id);
+    }
+    query.push(")");
+    let cards: HashMap<String, Card> = if card_ids.is_empty() {
+        HashMap::new()
+    } else {
+        query
+            .build_query_as::<Card>()
+            .fetch_all(&state.pool())
+            .await
+            .map_err(|e| CommandError::db("Failed to get cards", e))?
+            .into_iter()
+            .map(|card| (card.id.clone(), card))
+            .collect()
+    };
+
+    let missing: Vec<&str> = card_ids
+        .iter()
+        .filter(|id| !cards.contains_key(*id))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(CommandError::NotFound(format!("Cards not found: {}", missing.join(", "))));
+    }
+
+    let assets_dir = assets_root(&app, &state).await?;
+    let out_dir = PathBuf::from(&out_dir);
+    std::fs::create_dir_all(&out_dir)
+        .map_err(|e| CommandError::io("Failed to create export folder", e))?;
+
+    let mut used_names = HashSet::new();
+    let mut used_images = HashSet::new();
+    let mut copied_images: HashMap<PathBuf, String> = HashMap::new();
+    let mut copy_error = None;
+    let mut written = Vec::with_capacity(card_ids.len());
+
+    for id in &card_ids {
+        let card = &cards[id];
+
+        let mut resolve_image = |src: &str| {
+            let Some(source) = local_image_path(src, &assets_dir) else {
+                return src.to_string();
+            };
+            if let Some(link) = copied_images.get(&source) {
+                return link.clone();
+            }
+            let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("image");
+            let extension = source.extension().and_then(|e| e.to_str()).unwrap_or("png");
+            let name = unique_name(stem, extension, &mut used_images);
+            let target = out_dir.join("assets").join(&name);
+            let copied = std::fs::create_dir_all(out_dir.join("assets"))
+                .and_then(|_| std::fs::copy(&source, &target));
+            if let Err(e) = copied {
+                copy_error.get_or_insert(CommandError::io(&format!("Failed to copy image {}", source.display()), e));
+                return src.to_string();
+            }
+            // Markdown links use `/` and need spaces escaped
+            let link = format!("assets/{}", name.replace(' ', "%20"));
+            copied_images.insert(source, link.clone());
+            link
+        };
+
+        let body = tiptap_to_markdown(&card.content, &mut resolve_image);
+        if let Some(e) = copy_error.take() {
+            return Err(e);
+        }
+        let markdown = match card.title.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            Some(title) => format!("# {}\n\n{}\n", title, body),
+            None => format!("{}\n", body),
+        };
+
+        let path = out_dir.join(unique_name(&note_filename(card), "md", &mut used_names));
+        std::fs::write(&path, markdown)
+            .map_err(|e| CommandError::io(&format!("Failed to write {}", path.display()), e))?;
+        written.push(path.to_string_lossy().to_string());
+    }
+
+    log::info!("Exported {} cards as Markdown to {:?}", written.len(), out_dir);
+    Ok(written)
+}
+
+/// A Markdown file that could not be imported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkdownImportFailure {
+    pub path: String,
+    pub error: CommandError,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkdownImportResult {
+    pub cards: Vec<Card>,
+    pub failed: Vec<MarkdownImportFailure>,
+}
+
+fn collect_image_srcs(node: &Value, out: &mut Vec<String>) {
+    if node.get("type").and_then(Value::as_str) == Some("image") {
+        if let Some(src) = node.pointer("/attrs/src").and_then(Value::as_str) {
+            out.push(src.to_string());
+        }
+    }
+    for child in node.get("content").and_then(Value::as_array).into_iter().flatten() {
+        collect_image_srcs(child, out);
+    }
+}
+
+fn rewrite_image_srcs(node: &mut Value, sources: &HashMap<String, String>) {
+    if node.get("type").and_then(Value::as_str) == Some("image") {
+        if let Some(src) = node.pointer_mut("/attrs/src") {
+            if let Some(new_src) = src.as_str().and_then(|s| sources.get(s)) {
+                *src = Value::String(new_src.clone());
+            }
+        }
+    }
+    for child in node.get_mut("content").and_then(Value::as_array_mut).into_iter().flatten() {
+        rewrite_image_srcs(child, sources);
+    }
+}
+
+/// Take the card title from the document: a leading H1 is removed and used as the title,
+/// otherwise the first H1 anywhere is used
+fn take_title(doc: &mut Value) -> Option<String> {
+    let is_h1 = |node: &Value| {
+        node.get("type").and_then(Value::as_str) == Some("heading")
+            && node.pointer("/attrs/level").and_then(Value::as_u64) == Some(1)
+    };
+    let blocks = doc.get_mut("content")?.as_array_mut()?;
+    let title = if blocks.first().is_some_and(is_h1) {
+        tiptap_to_plaintext(&blocks.remove(0).to_string())
+    } else {
+        tiptap_to_plaintext(&blocks.iter().find(|node| is_h1(node))?.to_string())
+    };
+    Some(title.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Import one Markdown file as a card; images it links to locally are copied into the assets folder
+/// The card is not indexed yet, `import_markdown` indexes all imported cards at once
+async fn import_markdown_file(
+    pool: &sqlx::SqlitePool,
+    assets_dir: &Path,
+    path: &Path,
+    board_id: &str,
+) -> Result<Card, CommandError> {
+    let markdown = std::fs::read_to_string(path)
+        .map_err(|e| CommandError::io("Failed to read file", e))?;
+    let mut doc = markdown_to_tiptap(&markdown);
+    let title = take_title(&mut doc).or_else(|| {
+        path.file_stem().and_then(|s| s.to_str()).map(str::to_string)
+    });
+
+    let mut srcs = Vec::new();
+    collect_image_srcs(&doc, &mut srcs);
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let mut written = Vec::new();
+    let result = async {
+        let mut sources = HashMap::new();
+        for src in srcs {
+            if sources.contains_key(&src) || src.contains("://") || src.starts_with("data:") {
+                continue;
+            }
+            let image = base_dir.join(percent_decode(&src));
+            if !image.is_file() {
+                log::warn!("Image not found, keeping link as is: {}", src);
+                continue;
+            }
+            let relative_path = import_asset_copy(&mut tx, assets_dir, &image, "image", &mut written).await?;
+            sources.insert(src, relative_path);
+        }
+        rewrite_image_srcs(&mut doc, &sources);
+
+        let new_card = NewCard {
+            id: uuid::Uuid::new_v4().to_string(),
+            title,
+            content: doc.to_string(),
+            content_type: "tiptap".to_string(),
+            color: None,
+            metadata: None,
+            x: None,
+            y: None,
+            width: None,
+            height: None,
+        };
+        let card = insert_card_row(&mut tx, board_id, new_card).await?;
+        tx.commit()
+            .await
+            .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+        Ok(card)
+    }
+    .await;
+
+    if result.is_err() {
+        for file in &written {
+            if let Err(e) = std::fs::remove_file(file) {
+                log::warn!("Failed to remove imported asset {:?}: {}", file, e);
+            }
+        }
+    }
+    result
+}
+
+async fn index_imported_cards(pool: &sqlx::SqlitePool, entities: &[IndexEntity]) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    search::index_entities(&mut tx, entities).await?;
+    tx.commit().await
+}
+
+/// Import Markdown files as cards on a board, one card per file
+/// The title comes from the first H1 (or the file name). Files that can't be
+/// imported are reported in `failed` instead of stopping the import
+#[command]
+pub async fn import_markdown(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    paths: Vec<String>,
+    board_id: String,
+) -> Result<MarkdownImportResult, CommandError> {
+    let pool = state.writer();
+    let board_exists = sqlx::query("SELECT 1 FROM boards WHERE id = ?")
+        .bind(&board_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| CommandError::db("Failed to look up board", e))?
+        .is_some();
+    if !board_exists {
+        return Err(CommandError::NotFound(format!("board not found: {}", board_id)));
+    }
+
+    let assets_dir = assets_root(&app, &state).await?;
+
+    let mut result = MarkdownImportResult { cards: Vec::new(), failed: Vec::new() };
+    for path in paths {
+        match import_markdown_file(&pool, &assets_dir, Path::new(&path), &board_id).await {
+            Ok(card) => result.cards.push(card),
+            Err(error) => {
+                log::warn!("Failed to import {}: {}", path, error);
+                result.failed.push(MarkdownImportFailure { path, error });
+            }
+        }
+    }
+
+    // The cards are already saved, so an indexing failure is only logged;
+    // `fts_rebuild_index` brings them back into search
+    let entities: Vec<IndexEntity> = result
+        .cards
+        .iter()
+        .map(|card| IndexEntity {
+            entity_type: "card".to_string(),
+            entity_id: card.id.clone(),
+            title: card.title.clone().unwrap_or_default(),
+            content: tiptap_to_plaintext(&card.content),
+            tags: String::new(),
+        })
+        .collect();
+    if let Err(e) = index_imported_cards(&pool, &entities).await {
+        log::warn!("Failed to index imported Markdown cards: {}", e);
+    }
+
+    log::info!(
+        "Imported {} Markdown files to board {} ({} failed)",
+        result.cards.len(),
+        board_id,
+        result.failed.len()
+    );
+    Ok(result)
+}
@@ -1 +1,16 @@
+pub mod app_state;
+pub mod backup;
+pub mod boards;
+pub mod bundle;
+pub mod cards;
+pub mod content;
+pub mod dashboard;
 pub mod database;
+pub mod html;
+pub mod markdown;
+pub mod projects;
+pub mod search;
+pub mod sync;
+pub mod tags;
+pub mod templates;
+pub mod watch;
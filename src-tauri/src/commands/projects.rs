@@ -0,0 +1,513 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::{command, State};
+
+use crate::db::AppState;
+use crate::error::CommandError;
+use crate::validate;
+
+// ============================================
+// Project Commands
+// ============================================
+
+pub(crate) const PROJECT_COLUMNS: &str =
+    "id, title, description, thumbnail_path, color, created_at, updated_at, settings, is_pinned";
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub thumbnail_path: Option<String>,
+    pub color: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub settings: Option<String>, // JSON
+    #[serde(default)] // missing from older bundles
+    pub is_pinned: bool,
+}
+
+/// Create a new project
+/// Timestamps are always assigned here, never taken from the caller; the title is
+/// trimmed and checked by `validate::title`
+#[command]
+pub async fn create_project(
+    state: State<'_, AppState>,
+    id: String,
+    title: String,
+    description: Option<String>,
+    thumbnail_path: Option<String>,
+    color: Option<String>,
+    settings: Option<String>,
+) -> Result<Project, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let title = validate::title("title", &title)?;
+
+    let project = sqlx::query_as::<_, Project>(&format!(
+        "INSERT INTO projects ({0}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, 0) RETURNING {0}",
+        PROJECT_COLUMNS
+    ))
+    .bind(&id)
+    .bind(&title)
+    .bind(&description)
+    .bind(&thumbnail_path)
+    .bind(&color)
+    .bind(now)
+    .bind(now)
+    .bind(&settings)
+    .fetch_one(&state.writer())
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+            CommandError::Conflict(format!("project already exists: {}", id))
+        }
+        _ => CommandError::db("Failed to create project", e),
+    })?;
+
+    log::info!("Created project: {}", project.id);
+    Ok(project)
+}
+
+/// Get all projects, pinned ones first, then most recently updated first
+/// `limit`/`offset` allow the project grid to paginate
+#[command]
+pub async fn get_projects(
+    state: State<'_, AppState>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<Project>, CommandError> {
+    // SQLite treats a negative LIMIT as "no limit"
+    let projects = sqlx::query_as::<_, Project>(&format!(
+        "SELECT {} FROM projects ORDER BY is_pinned DESC, updated_at DESC LIMIT ? OFFSET ?",
+        PROJECT_COLUMNS
+    ))
+    .bind(limit.unwrap_or(-1))
+    .bind(offset.unwrap_or(0))
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get projects", e))?;
+
+    Ok(projects)
+}
+
+/// Get a single project by id, with its settings JSON as stored
+#[command]
+pub async fn get_project(state: State<'_, AppState>, id: String) -> Result<Option<Project>, CommandError> {
+    let project = sqlx::query_as::<_, Project>(&format!(
+        "SELECT {} FROM projects WHERE id = ?",
+        PROJECT_COLUMNS
+    ))
+    .bind(&id)
+    .fetch_optional(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get project", e))?;
+
+    Ok(project)
+}
+
+/// Update a project, touching only the fields that were provided
+#[command]
+pub async fn update_project(
+    state: State<'_, AppState>,
+    id: String,
+    title: Option<String>,
+    description: Option<String>,
+    thumbnail_path: Option<String>,
+    color: Option<String>,
+    settings: Option<String>,
+) -> Result<Project, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let title = validate::optional_title("title", title)?;
+
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new("UPDATE projects SET updated_at = ");
+    query.push_bind(now);
+
+    let fields = [
+        ("title", title),
+        ("description", description),
+        ("thumbnail_path", thumbnail_path),
+        ("color", color),
+        ("settings", settings),
+    ];
+    for (column, value) in fields {
+        if let Some(value) = value {
+            query.push(format!(", {} = ", column)).push_bind(value);
+        }
+    }
+
+    query.push(" WHERE id = ").push_bind(&id);
+    query.push(format!(" RETURNING {}", PROJECT_COLUMNS));
+
+    let project = query
+        .build_query_as::<Project>()
+        .fetch_optional(&state.writer())
+        .await
+        .map_err(|e| CommandError::db("Failed to update project", e))?
+        .ok_or_else(|| CommandError::NotFound("project not found".to_string()))?;
+
+    log::info!("Updated project: {}", project.id);
+    Ok(project)
+}
+
+/// Pin a project to the top of `get_projects`, or unpin it
+#[command]
+pub async fn set_project_pinned(state: State<'_, AppState>, id: String, pinned: bool) -> Result<Project, CommandError> {
+    let project = sqlx::query_as::<_, Project>(&format!(
+        "UPDATE projects SET is_pinned = ?, updated_at = ? WHERE id = ? RETURNING {}",
+        PROJECT_COLUMNS
+    ))
+    .bind(pinned)
+    .bind(chrono::Utc::now().timestamp_millis())
+    .bind(&id)
+    .fetch_optional(&state.writer())
+    .await
+    .map_err(|e| CommandError::db("Failed to pin project", e))?
+    .ok_or_else(|| CommandError::NotFound(format!("project not found: {}", id)))?;
+
+    log::info!("{} project: {}", if pinned { "Pinned" } else { "Unpinned" }, id);
+    Ok(project)
+}
+
+/// Delete a project along with its boards, their cards and search index entries
+/// Cards also placed on a board of another project are kept, as in `delete_board`.
+/// Returns false if no project with this id existed
+#[command]
+pub async fn delete_project(state: State<'_, AppState>, id: String) -> Result<bool, CommandError> {
+    // Cards placed only on boards of the project
+    const PROJECT_CARDS: &str = "SELECT bc.card_id FROM board_cards bc
+         JOIN boards b ON b.id = bc.board_id
+         WHERE b.project_id = ?1
+         EXCEPT
+         SELECT bc.card_id FROM board_cards bc
+         JOIN boards b ON b.id = bc.board_id
+         WHERE b.project_id IS NOT ?1";
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let tombstones = [
+        format!("SELECT 'card', card_id, ?2 FROM ({})", PROJECT_CARDS),
+        "SELECT 'board', id, ?2 FROM boards WHERE project_id = ?1".to_string(),
+        "SELECT 'project', id, ?2 FROM projects WHERE id = ?1".to_string(),
+    ];
+    for select in &tombstones {
+        sqlx::query(&format!("INSERT OR REPLACE INTO tombstones (entity_type, entity_id, deleted_at) {}", select))
+            .bind(&id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to record deletion", e))?;
+    }
+
+    let statements = [
+        format!("DELETE FROM search_index WHERE entity_type = 'card' AND entity_id IN ({})", PROJECT_CARDS),
+        format!("DELETE FROM cards WHERE id IN ({})", PROJECT_CARDS),
+        "DELETE FROM search_index WHERE entity_type = 'board' AND entity_id IN (SELECT id FROM boards WHERE project_id = ?)".to_string(),
+        "DELETE FROM search_index WHERE entity_type = 'project' AND entity_id = ?".to_string(),
+        "DELETE FROM boards WHERE project_id = ?".to_string(),
+    ];
+    for sql in &statements {
+        sqlx::query(sql)
+            .bind(&id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to delete project data", e))?;
+    }
+
+    let deleted = sqlx::query("DELETE FROM projects WHERE id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to delete project", e))?
+        .rows_affected()
+        > 0;
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    if deleted {
+        log::info!("Deleted project: {}", id);
+    } else {
+        log::warn!("Project not found: {}", id);
+    }
+    Ok(deleted)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStats {
+    pub board_count: i64,
+    pub card_count: i64,
+    pub word_count: i64,
+    pub last_updated_at: i64, // latest update to the project, its boards or their cards
+    pub cards_by_content_type: HashMap<String, i64>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ProjectStatsRow {
+    board_count: i64,
+    card_count: i64,
+    word_count: i64,
+    last_updated_at: i64,
+    cards_by_content_type: String, // JSON object
+}
+
+/// Get board, card and word totals for a project, computed in a single query
+/// Cards on several boards are counted once; trashed cards are left out
+#[command]
+pub async fn get_project_stats(state: State<'_, AppState>, project_id: String) -> Result<ProjectStats, CommandError> {
+    let row = sqlx::query_as::<_, ProjectStatsRow>(
+        "WITH project_boards AS (
+             SELECT id, updated_at FROM boards WHERE project_id = ?1
+         ),
+         project_cards AS (
+             SELECT id, COALESCE(content_type, 'tiptap') AS content_type, word_count, updated_at
+             FROM cards
+             WHERE deleted_at IS NULL
+               AND id IN (SELECT card_id FROM board_cards WHERE board_id IN (SELECT id FROM project_boards))
+         )
+         SELECT
+             (SELECT COUNT(*) FROM project_boards) AS board_count,
+             (SELECT COUNT(*) FROM project_cards) AS card_count,
+             (SELECT COALESCE(SUM(word_count), 0) FROM project_cards) AS word_count,
+             MAX(p.updated_at,
+                 COALESCE((SELECT MAX(updated_at) FROM project_boards), 0),
+                 COALESCE((SELECT MAX(updated_at) FROM project_cards), 0)) AS last_updated_at,
+             (SELECT json_group_object(content_type, total) FROM
+                 (SELECT content_type, COUNT(*) AS total FROM project_cards GROUP BY content_type)
+             ) AS cards_by_content_type
+         FROM projects p WHERE p.id = ?1",
+    )
+    .bind(&project_id)
+    .fetch_optional(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get project stats", e))?
+    .ok_or_else(|| CommandError::NotFound(format!("project not found: {}", project_id)))?;
+
+    let cards_by_content_type = serde_json::from_str(&row.cards_by_content_type)
+        .map_err(|e| CommandError::internal("Failed to get project stats", e))?;
+
+    Ok(ProjectStats {
+        board_count: row.board_count,
+        card_count: row.card_count,
+        word_count: row.word_count,
+        last_updated_at: row.last_updated_at,
+        cards_by_content_type,
+    })
+}
+
+/// Most colors a project palette can hold
+const MAX_PALETTE_COLORS: usize = 64;
+
+/// Check a palette fits in `MAX_PALETTE_COLORS` and holds only hex colors
+fn validate_palette(colors: &[String]) -> Result<Vec<String>, CommandError> {
+    if colors.len() > MAX_PALETTE_COLORS {
+        return Err(CommandError::Validation(format!(
+            "palette has too many colors (at most {})",
+            MAX_PALETTE_COLORS
+        )));
+    }
+    colors
+        .iter()
+        .map(|color| validate::hex_color("color", color))
+        .collect()
+}
+
+/// Replace the color palette stored under `palette` in the project's settings
+/// Other settings keys are left as they are. Returns the stored colors
+#[command]
+pub async fn set_project_palette(
+    state: State<'_, AppState>,
+    project_id: String,
+    colors: Vec<String>,
+) -> Result<Vec<String>, CommandError> {
+    let colors = validate_palette(&colors)?;
+    let palette = serde_json::to_string(&colors)
+        .map_err(|e| CommandError::internal("Failed to encode palette", e))?;
+
+    let updated = sqlx::query(
+        "UPDATE projects SET settings = json_set(COALESCE(settings, '{}'), '$.palette', json(?)), updated_at = ?
+         WHERE id = ?",
+    )
+    .bind(&palette)
+    .bind(chrono::Utc::now().timestamp_millis())
+    .bind(&project_id)
+    .execute(&state.writer())
+    .await
+    .map_err(|e| CommandError::db("Failed to save palette", e))?
+    .rows_affected()
+        > 0;
+    if !updated {
+        return Err(CommandError::NotFound(format!("project not found: {}", project_id)));
+    }
+
+    log::info!("Saved palette of {} colors for project: {}", colors.len(), project_id);
+    Ok(colors)
+}
+
+/// Get the color palette from the project's settings, empty if none was saved
+#[command]
+pub async fn get_project_palette(state: State<'_, AppState>, project_id: String) -> Result<Vec<String>, CommandError> {
+    let palette: Option<String> = sqlx::query_scalar("SELECT json_extract(settings, '$.palette') FROM projects WHERE id = ?")
+        .bind(&project_id)
+        .fetch_optional(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to get palette", e))?
+        .ok_or_else(|| CommandError::NotFound(format!("project not found: {}", project_id)))?;
+
+    match palette {
+        Some(palette) => serde_json::from_str(&palette)
+            .map_err(|e| CommandError::internal("Failed to read palette", e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// How many of a project's cards use one color
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorCount {
+    pub color: Option<String>, // None for cards without a color
+    pub card_count: i64,
+}
+
+/// Count the project's cards by color, most used first
+/// Cards without a color are one bucket with no color; cards on several boards are
+/// counted once and trashed cards are left out
+#[command]
+pub async fn get_color_usage(state: State<'_, AppState>, project_id: String) -> Result<Vec<ColorCount>, CommandError> {
+    let project_exists = sqlx::query("SELECT 1 FROM projects WHERE id = ?")
+        .bind(&project_id)
+        .fetch_optional(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to look up project", e))?
+        .is_some();
+    if !project_exists {
+        return Err(CommandError::NotFound(format!("project not found: {}", project_id)));
+    }
+
+    sqlx::query_as::<_, ColorCount>(
+        "SELECT color, COUNT(*) AS card_count FROM cards
+         WHERE deleted_at IS NULL
+           AND id IN (SELECT bc.card_id FROM board_cards bc JOIN boards b ON b.id = bc.board_id
+                      WHERE b.project_id = ?)
+         GROUP BY color
+         ORDER BY card_count DESC, color ASC",
+    )
+    .bind(&project_id)
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get color usage", e))
+}
+
+/// Typed view of a project's `settings` JSON
+/// Missing fields take their defaults; keys this version doesn't know are kept in
+/// `extra` so saving never drops them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProjectSettings {
+    /// Same list as `get_project_palette`
+    pub palette: Vec<String>,
+    /// Hex color given to new cards
+    pub default_card_color: Option<String>,
+    /// Content type given to new cards, one of `CARD_CONTENT_TYPES`
+    pub default_content_type: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Get a project's settings, with defaults for anything not saved yet
+#[command]
+pub async fn get_project_settings(state: State<'_, AppState>, project_id: String) -> Result<ProjectSettings, CommandError> {
+    let settings: Option<String> = sqlx::query_scalar("SELECT settings FROM projects WHERE id = ?")
+        .bind(&project_id)
+        .fetch_optional(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to get project settings", e))?
+        .ok_or_else(|| CommandError::NotFound(format!("project not found: {}", project_id)))?;
+
+    match settings.filter(|settings| !settings.trim().is_empty()) {
+        Some(settings) => serde_json::from_str(&settings)
+            .map_err(|e| CommandError::internal("Failed to read project settings", e)),
+        None => Ok(ProjectSettings::default()),
+    }
+}
+
+/// Replace a project's settings; colors and the content type are validated first
+/// Returns the settings as stored
+#[command]
+pub async fn set_project_settings(
+    state: State<'_, AppState>,
+    project_id: String,
+    settings: ProjectSettings,
+) -> Result<ProjectSettings, CommandError> {
+    let settings = ProjectSettings {
+        palette: validate_palette(&settings.palette)?,
+        default_card_color: settings
+            .default_card_color
+            .map(|color| validate::hex_color("defaultCardColor", &color))
+            .transpose()?,
+        default_content_type: settings
+            .default_content_type
+            .map(|content_type| validate::content_type(Some(content_type)))
+            .transpose()?,
+        extra: settings.extra,
+    };
+    let json = serde_json::to_string(&settings)
+        .map_err(|e| CommandError::internal("Failed to encode project settings", e))?;
+
+    let updated = sqlx::query("UPDATE projects SET settings = ?, updated_at = ? WHERE id = ?")
+        .bind(&json)
+        .bind(chrono::Utc::now().timestamp_millis())
+        .bind(&project_id)
+        .execute(&state.writer())
+        .await
+        .map_err(|e| CommandError::db("Failed to save project settings", e))?
+        .rows_affected()
+        > 0;
+    if !updated {
+        return Err(CommandError::NotFound(format!("project not found: {}", project_id)));
+    }
+
+    log::info!("Saved settings for project: {}", project_id);
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use tauri::Manager;
+
+    use super::*;
+    use crate::commands::boards;
+    use crate::test_support::{seed_card, seed_card_on_board, test_app};
+
+    #[tokio::test]
+    async fn delete_project_keeps_cards_placed_in_other_projects() {
+        let (app, _dir) = test_app().await;
+        seed_card(&app, "only").await;
+        seed_card_on_board(&app, "shared").await;
+        create_project(app.state(), "q".into(), "Q".into(), None, None, None, None)
+            .await
+            .unwrap();
+        boards::create_board(app.state(), "other".into(), "q".into(), None, "Other".into(), None)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO board_cards (board_id, card_id, x, y, width, height, created_at) VALUES ('other', 'shared', 0, 0, 100, 100, 0)")
+            .execute(&app.state::<AppState>().writer())
+            .await
+            .unwrap();
+
+        assert!(delete_project(app.state(), "p".into()).await.unwrap());
+
+        let remaining: Vec<String> = sqlx::query_scalar("SELECT id FROM cards ORDER BY id")
+            .fetch_all(&app.state::<AppState>().pool())
+            .await
+            .unwrap();
+        assert_eq!(remaining, vec!["shared".to_string()]);
+    }
+}
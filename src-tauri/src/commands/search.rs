@@ -0,0 +1,881 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use tauri::{command, State};
+
+use super::content::card_plaintext;
+use super::database::asset_display_name;
+use crate::db::AppState;
+use crate::error::CommandError;
+
+// ============================================
+// Full-Text Search (FTS5)
+// ============================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct FTSSearchResult {
+    pub entity_type: String,
+    pub entity_id: String,
+    pub title: String,
+    pub snippet: String,
+    pub rank: f64,
+}
+
+/// FTS5 operators that are dropped from plain (non-raw) queries
+const FTS_OPERATORS: [&str; 4] = ["AND", "OR", "NOT", "NEAR"];
+
+/// Terms of free-form user input: bare operators and punctuation-only words are dropped
+fn query_terms(query: &str) -> Vec<&str> {
+    query
+        .split_whitespace()
+        .filter(|term| !FTS_OPERATORS.contains(term))
+        .filter(|term| term.chars().any(char::is_alphanumeric))
+        .collect()
+}
+
+/// Quote a term as an FTS5 string so none of its characters are parsed as syntax
+fn quote_fts_term(term: &str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
+
+/// Turn free-form user input into a safe FTS5 query
+/// Every term is quoted so punctuation like `"`, `(`, `*` or `-` can't be parsed
+/// as FTS5 syntax, bare operators are dropped and the terms are implicitly ANDed
+pub(crate) fn sanitize_fts_query(query: &str) -> String {
+    query_terms(query)
+        .into_iter()
+        .map(quote_fts_term)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Like `sanitize_fts_query`, but every term also matches words it is a prefix of
+fn prefix_fts_query(query: &str) -> String {
+    query_terms(query)
+        .into_iter()
+        .map(|term| format!("{}*", quote_fts_term(term)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Indexed words kept per query term in fuzzy mode
+const FUZZY_CANDIDATES: i64 = 5;
+
+/// Largest `editdist3` cost accepted in fuzzy mode: one edit (100) for short terms, two for longer ones
+fn fuzzy_max_cost(term: &str) -> i64 {
+    if term.chars().count() <= 4 {
+        100
+    } else {
+        200
+    }
+}
+
+/// Whether the spellfix1 extension (which provides `editdist3`) is loaded
+async fn has_spellfix(pool: &sqlx::SqlitePool) -> bool {
+    sqlx::query("SELECT editdist3('a', 'b')").fetch_one(pool).await.is_ok()
+}
+
+/// Levenshtein distance between two words, in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Indexed words close to `term` without spellfix1: words of about the same length
+/// sharing its first character are read from `search_vocab` and compared in Rust with
+/// the same budget as `editdist3` (one edit per 100 of `fuzzy_max_cost`)
+async fn vocab_neighbours(pool: &sqlx::SqlitePool, term: &str) -> Result<Vec<String>, CommandError> {
+    let Some(first) = term.chars().next() else {
+        return Ok(Vec::new());
+    };
+    let max_edits = (fuzzy_max_cost(term) / 100) as usize;
+    let length = term.chars().count();
+
+    let words: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT term, doc FROM search_vocab
+         WHERE term >= ?1 AND term < ?2 AND length(term) BETWEEN ?3 AND ?4",
+    )
+    .bind(first.to_string())
+    .bind(format!("{}\u{10FFFF}", first))
+    .bind(length.saturating_sub(max_edits) as i64)
+    .bind((length + max_edits) as i64)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| CommandError::db("Search failed", e))?;
+
+    let mut close: Vec<(usize, i64, String)> = words
+        .into_iter()
+        .filter(|(word, _)| word != term)
+        .filter_map(|(word, docs)| {
+            let distance = edit_distance(term, &word);
+            (distance <= max_edits).then_some((distance, -docs, word))
+        })
+        .collect();
+    close.sort();
+    Ok(close.into_iter().take(FUZZY_CANDIDATES as usize).map(|(_, _, word)| word).collect())
+}
+
+/// Like `prefix_fts_query`, but every term is also ORed with the indexed words
+/// (from the `search_vocab` fts5vocab table) within a small edit distance of it
+/// With `spellfix` the distance is `editdist3` in SQL, otherwise `vocab_neighbours`
+async fn fuzzy_fts_query(pool: &sqlx::SqlitePool, query: &str, spellfix: bool) -> Result<String, CommandError> {
+    let mut groups = Vec::new();
+    for term in query_terms(query) {
+        let lowered = term.to_lowercase();
+        let candidates: Vec<String> = if spellfix {
+            sqlx::query_scalar(
+                "SELECT term FROM search_vocab
+                 WHERE term != ?1 AND editdist3(term, ?1) <= ?2
+                 ORDER BY editdist3(term, ?1), doc DESC
+                 LIMIT ?3",
+            )
+            .bind(&lowered)
+            .bind(fuzzy_max_cost(&lowered))
+            .bind(FUZZY_CANDIDATES)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| CommandError::db("Search failed", e))?
+        } else {
+            vocab_neighbours(pool, &lowered).await?
+        };
+
+        let alternatives = std::iter::once(format!("{}*", quote_fts_term(term)))
+            .chain(candidates.iter().map(|candidate| quote_fts_term(candidate)))
+            .collect::<Vec<_>>();
+        groups.push(format!("({})", alternatives.join(" OR ")));
+    }
+    Ok(groups.join(" "))
+}
+
+/// Largest snippet window FTS5 accepts, in tokens
+const MAX_SNIPPET_TOKENS: i64 = 64;
+
+/// One page of search results plus the total number of matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PagedSearch {
+    pub results: Vec<FTSSearchResult>,
+    pub total: i64,
+}
+
+/// Append the MATCH and filter conditions shared by the search and count queries
+fn push_search_filters<'a>(
+    sql: &mut sqlx::QueryBuilder<'a, sqlx::Sqlite>,
+    query: &'a str,
+    types: &'a [String],
+    date_from: Option<i64>,
+    date_to: Option<i64>,
+    project_id: Option<&'a str>,
+) {
+    sql.push(" WHERE search_index MATCH ").push_bind(query);
+    if !types.is_empty() {
+        sql.push(" AND entity_type IN (");
+        let mut separated = sql.separated(", ");
+        for entity_type in types {
+            separated.push_bind(entity_type);
+        }
+        separated.push_unseparated(")");
+    }
+    if let Some(date_from) = date_from {
+        sql.push(" AND indexed_at >= ").push_bind(date_from);
+    }
+    if let Some(date_to) = date_to {
+        sql.push(" AND indexed_at <= ").push_bind(date_to);
+    }
+    if let Some(project_id) = project_id {
+        const PROJECT_CARDS: &str =
+            "SELECT bc.card_id FROM board_cards bc JOIN boards b ON b.id = bc.board_id WHERE b.project_id = ";
+        sql.push(" AND ((entity_type = 'project' AND entity_id = ").push_bind(project_id);
+        sql.push(") OR (entity_type = 'board' AND entity_id IN (SELECT id FROM boards WHERE project_id = ")
+            .push_bind(project_id);
+        sql.push(")) OR (entity_type = 'card' AND entity_id IN (").push(PROJECT_CARDS).push_bind(project_id);
+        sql.push(")) OR (entity_type = 'file' AND entity_id IN (SELECT a.id FROM assets a
+             JOIN asset_refs r ON r.relative_path = a.relative_path WHERE r.card_id IN (")
+            .push(PROJECT_CARDS)
+            .push_bind(project_id);
+        sql.push("))) OR (entity_type = 'highlight' AND entity_id IN (SELECT id FROM highlights
+             WHERE source_type = 'card' AND source_id IN (")
+            .push(PROJECT_CARDS)
+            .push_bind(project_id);
+        sql.push("))))");
+    }
+}
+
+/// Search the FTS5 index, best matches first
+/// `types` restricts entity types (empty means all), `date_from`/`date_to`
+/// filter on when the entity was indexed (ms timestamps, inclusive).
+/// `project_id` keeps the project itself, its boards and their cards, the files those
+/// cards embed and highlights on them; journal entries belong to no project.
+/// The query is sanitized unless `raw` is set, in which case FTS5 syntax
+/// (operators, prefixes, column filters) is passed through as-is and `mode` is ignored.
+/// `mode` picks how sanitized terms match:
+/// - "exact" (default): whole words only
+/// - "prefix": words starting with each term, no extension needed
+/// - "fuzzy": prefix matches plus indexed words a typo or two away; uses `editdist3`
+///   when the spellfix1 extension is loaded and compares words in Rust otherwise
+/// `limit` (default 50) and `offset` select a page; `total` counts every match.
+/// Snippets wrap matches in `open_tag`/`close_tag` (default `<b>`/`</b>`) and span
+/// about `snippet_tokens` tokens (default 20, at most 64)
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn fts_search(
+    state: State<'_, AppState>,
+    query: String,
+    types: Option<Vec<String>>,
+    date_from: Option<i64>,
+    date_to: Option<i64>,
+    project_id: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    raw: Option<bool>,
+    mode: Option<String>,
+    open_tag: Option<String>,
+    close_tag: Option<String>,
+    snippet_tokens: Option<i64>,
+) -> Result<PagedSearch, CommandError> {
+    let pool = state.pool();
+    let typed = query.trim().to_string();
+    let raw = raw.unwrap_or(false);
+    let mode = mode.as_deref().unwrap_or("exact");
+    if !raw && !matches!(mode, "exact" | "prefix" | "fuzzy") {
+        return Err(CommandError::Validation(format!(
+            "Unknown search mode: {} (expected exact, prefix or fuzzy)",
+            mode
+        )));
+    }
+    // Checked before fuzzy mode looks up the vocabulary, so nothing touches the database
+    if typed.is_empty() {
+        return Ok(PagedSearch { results: vec![], total: 0 });
+    }
+
+    let query = if raw {
+        typed.clone()
+    } else {
+        match mode {
+            "prefix" => prefix_fts_query(&query),
+            "fuzzy" => fuzzy_fts_query(&pool, &query, has_spellfix(&pool).await).await?,
+            _ => sanitize_fts_query(&query),
+        }
+    };
+    if query.is_empty() {
+        return Ok(PagedSearch { results: vec![], total: 0 });
+    }
+    let types = types.unwrap_or_default();
+
+    // The markers are bound as parameters, so any text is safe to pass
+    let open_tag = open_tag.unwrap_or_else(|| "<b>".to_string());
+    let close_tag = close_tag.unwrap_or_else(|| "</b>".to_string());
+    let snippet_tokens = snippet_tokens.unwrap_or(20).clamp(1, MAX_SNIPPET_TOKENS);
+
+    let mut sql = sqlx::QueryBuilder::<sqlx::Sqlite>::new(
+        "SELECT entity_type, entity_id, title, snippet(search_index, 3, ",
+    );
+    sql.push_bind(&open_tag).push(", ").push_bind(&close_tag);
+    sql.push(", '...', ").push_bind(snippet_tokens);
+    sql.push(") AS snippet, rank FROM search_index");
+    push_search_filters(&mut sql, &query, &types, date_from, date_to, project_id.as_deref());
+    sql.push(" ORDER BY rank LIMIT ").push_bind(limit.unwrap_or(50));
+    sql.push(" OFFSET ").push_bind(offset.unwrap_or(0));
+
+    let results = sql
+        .build_query_as::<FTSSearchResult>()
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| CommandError::db("Search failed", e))?;
+
+    let mut count = sqlx::QueryBuilder::<sqlx::Sqlite>::new("SELECT COUNT(*) FROM search_index");
+    push_search_filters(&mut count, &query, &types, date_from, date_to, project_id.as_deref());
+    let total = count
+        .build_query_scalar::<i64>()
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| CommandError::db("Search failed", e))?;
+
+    // Later pages of the same search aren't new queries
+    if offset.unwrap_or(0) == 0 {
+        if let Err(e) = record_search(&state, &typed, total).await {
+            log::warn!("Failed to record search history: {}", e);
+        }
+    }
+
+    Ok(PagedSearch { results, total })
+}
+
+/// Quick search across every entity type with no filters
+/// Same ranking and snippets as `fts_search`, which remains the advanced entry point,
+/// and recorded in the search history the same way
+#[command]
+pub async fn search_content(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<i64>,
+) -> Result<Vec<FTSSearchResult>, CommandError> {
+    let page = fts_search(state, query, None, None, None, None, limit, None, None, None, None, None, None).await?;
+    Ok(page.results)
+}
+
+/// Replace the search index entry for an entity
+/// FTS5 tables have no unique key, so the old row is deleted before inserting
+pub(crate) async fn index_entity(
+    conn: &mut SqliteConnection,
+    entity_type: &str,
+    entity_id: &str,
+    title: &str,
+    content: &str,
+    tags: &str,
+) -> Result<(), sqlx::Error> {
+    remove_entity(&mut *conn, entity_type, entity_id).await?;
+    insert_entry(conn, entity_type, entity_id, title, content, tags).await
+}
+
+/// Insert a search index row without removing any previous entry
+async fn insert_entry(
+    conn: &mut SqliteConnection,
+    entity_type: &str,
+    entity_id: &str,
+    title: &str,
+    content: &str,
+    tags: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO search_index (entity_type, entity_id, title, content, tags, indexed_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(entity_type)
+    .bind(entity_id)
+    .bind(title)
+    .bind(content)
+    .bind(tags)
+    .bind(chrono::Utc::now().timestamp_millis())
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// One entity to add to the search index with `fts_index_batch`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexEntity {
+    pub entity_type: String,
+    pub entity_id: String,
+    pub title: String,
+    pub content: String,
+    #[serde(default)]
+    pub tags: String,
+}
+
+/// Replace the search index entries of many entities on the caller's connection/transaction
+/// The same two statements run for every entity, so each is prepared once and reused
+/// from the connection's statement cache. Returns how many were indexed
+pub(crate) async fn index_entities(conn: &mut SqliteConnection, entities: &[IndexEntity]) -> Result<u64, sqlx::Error> {
+    let indexed_at = chrono::Utc::now().timestamp_millis();
+    for entity in entities {
+        sqlx::query("DELETE FROM search_index WHERE entity_type = ? AND entity_id = ?")
+            .bind(&entity.entity_type)
+            .bind(&entity.entity_id)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query(
+            "INSERT INTO search_index (entity_type, entity_id, title, content, tags, indexed_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&entity.entity_type)
+        .bind(&entity.entity_id)
+        .bind(&entity.title)
+        .bind(&entity.content)
+        .bind(&entity.tags)
+        .bind(indexed_at)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(entities.len() as u64)
+}
+
+/// Remove the search index entry for an entity
+pub(crate) async fn remove_entity(
+    conn: &mut SqliteConnection,
+    entity_type: &str,
+    entity_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM search_index WHERE entity_type = ? AND entity_id = ?")
+        .bind(entity_type)
+        .bind(entity_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Content text stored in the search index for an entity, None if it isn't indexed
+pub(crate) async fn indexed_content(
+    conn: &mut SqliteConnection,
+    entity_type: &str,
+    entity_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT content FROM search_index WHERE entity_type = ? AND entity_id = ?")
+        .bind(entity_type)
+        .bind(entity_id)
+        .fetch_optional(&mut *conn)
+        .await
+}
+
+/// Entity kinds that can be stored in the search index
+const INDEXED_ENTITY_TYPES: [&str; 6] =
+    ["project", "board", "card", "file", "highlight", "journal"];
+
+fn validate_entity_type(entity_type: &str) -> Result<(), CommandError> {
+    if INDEXED_ENTITY_TYPES.contains(&entity_type) {
+        Ok(())
+    } else {
+        Err(CommandError::Validation(format!("Unknown entity type: {}", entity_type)))
+    }
+}
+
+/// Add or replace an entity in the search index
+#[command]
+pub async fn fts_index_entity(
+    state: State<'_, AppState>,
+    entity_type: String,
+    entity_id: String,
+    title: String,
+    content: String,
+    tags: String,
+) -> Result<(), CommandError> {
+    validate_entity_type(&entity_type)?;
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    index_entity(&mut tx, &entity_type, &entity_id, &title, &content, &tags)
+        .await
+        .map_err(|e| CommandError::db(&format!("Failed to index {}", entity_type), e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Indexed {}: {}", entity_type, entity_id);
+    Ok(())
+}
+
+/// Add or replace many entities in the search index in one transaction
+/// Every entity type is checked first; the first unknown one fails the whole batch
+/// and nothing is indexed. Returns how many entities were indexed
+#[command]
+pub async fn fts_index_batch(state: State<'_, AppState>, entities: Vec<IndexEntity>) -> Result<u64, CommandError> {
+    for (i, entity) in entities.iter().enumerate() {
+        validate_entity_type(&entity.entity_type).map_err(|e| {
+            CommandError::Validation(format!("{} (entity {}: {})", e, i, entity.entity_id))
+        })?;
+    }
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let indexed = index_entities(&mut tx, &entities)
+        .await
+        .map_err(|e| CommandError::db("Failed to index entities", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Indexed {} entities", indexed);
+    Ok(indexed)
+}
+
+/// Remove an entity from the search index
+#[command]
+pub async fn fts_remove_entity(
+    state: State<'_, AppState>,
+    entity_type: String,
+    entity_id: String,
+) -> Result<(), CommandError> {
+    validate_entity_type(&entity_type)?;
+
+    let mut conn = state.writer()
+        .acquire()
+        .await
+        .map_err(|e| CommandError::db("Failed to acquire connection", e))?;
+
+    remove_entity(&mut conn, &entity_type, &entity_id)
+        .await
+        .map_err(|e| CommandError::db(&format!("Failed to remove {} from index", entity_type), e))?;
+
+    log::info!("Removed {} from index: {}", entity_type, entity_id);
+    Ok(())
+}
+
+/// Entities indexed by a rebuild, per type
+/// With `dry_run` set these are the counts a rebuild would index; nothing was written
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexRebuildSummary {
+    pub projects: u64,
+    pub boards: u64,
+    pub cards: u64,
+    pub files: u64,
+    pub total: u64,
+    pub dry_run: bool,
+}
+
+/// Count what `fts_rebuild_index` would index, using the same filters
+async fn count_rebuild_entities(pool: &sqlx::SqlitePool) -> Result<IndexRebuildSummary, CommandError> {
+    let (projects, boards, cards, files): (i64, i64, i64, i64) = sqlx::query_as(
+        "SELECT (SELECT COUNT(*) FROM projects),
+                (SELECT COUNT(*) FROM boards),
+                (SELECT COUNT(*) FROM cards WHERE deleted_at IS NULL),
+                (SELECT COUNT(*) FROM assets)",
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| CommandError::db("Failed to count entities", e))?;
+
+    let mut summary = IndexRebuildSummary {
+        projects: projects as u64,
+        boards: boards as u64,
+        cards: cards as u64,
+        files: files as u64,
+        total: 0,
+        dry_run: true,
+    };
+    summary.total = summary.projects + summary.boards + summary.cards + summary.files;
+    Ok(summary)
+}
+
+/// Rebuild the whole search index from projects, boards, cards and files
+/// Files use the PDF text stored when they were imported; nothing is re-extracted
+/// Runs in a single transaction so search never sees a half-built index.
+/// With `dry_run` only the entities are counted and the index is left untouched
+#[command]
+pub async fn fts_rebuild_index(
+    state: State<'_, AppState>,
+    dry_run: Option<bool>,
+) -> Result<IndexRebuildSummary, CommandError> {
+    // Cards are read in pages so large libraries aren't loaded all at once
+    const CARD_BATCH_SIZE: i64 = 500;
+
+    if dry_run.unwrap_or(false) {
+        return count_rebuild_entities(&state.pool()).await;
+    }
+
+    let mut summary = IndexRebuildSummary::default();
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    sqlx::query("DELETE FROM search_index")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to clear search index", e))?;
+
+    let projects: Vec<(String, String, Option<String>)> =
+        sqlx::query_as("SELECT id, title, description FROM projects")
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to read projects", e))?;
+    for (id, title, description) in projects {
+        insert_entry(&mut tx, "project", &id, &title, description.as_deref().unwrap_or_default(), "")
+            .await
+            .map_err(|e| CommandError::db("Failed to index project", e))?;
+        summary.projects += 1;
+    }
+
+    let boards: Vec<(String, String)> = sqlx::query_as("SELECT id, title FROM boards")
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to read boards", e))?;
+    for (id, title) in boards {
+        insert_entry(&mut tx, "board", &id, &title, "", "")
+            .await
+            .map_err(|e| CommandError::db("Failed to index board", e))?;
+        summary.boards += 1;
+    }
+
+    let mut last_id = String::new();
+    loop {
+        let cards: Vec<(String, Option<String>, Option<String>, String)> = sqlx::query_as(
+            "SELECT id, title, content, COALESCE(content_type, 'tiptap') FROM cards
+             WHERE id > ? AND deleted_at IS NULL
+             ORDER BY id LIMIT ?",
+        )
+        .bind(&last_id)
+        .bind(CARD_BATCH_SIZE)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to read cards", e))?;
+
+        let Some((id, _, _, _)) = cards.last() else { break };
+        last_id = id.clone();
+
+        for (id, title, content, content_type) in cards {
+            let plaintext = card_plaintext(&content_type, content.as_deref().unwrap_or_default());
+            insert_entry(&mut tx, "card", &id, title.as_deref().unwrap_or_default(), &plaintext, "")
+                .await
+                .map_err(|e| CommandError::db("Failed to index card", e))?;
+            summary.cards += 1;
+        }
+    }
+
+    let files: Vec<(String, String, Option<String>)> =
+        sqlx::query_as("SELECT id, relative_path, extracted_text FROM assets")
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to read assets", e))?;
+    for (id, relative_path, text) in files {
+        let name = asset_display_name(&relative_path);
+        insert_entry(&mut tx, "file", &id, name, text.as_deref().unwrap_or_default(), "")
+            .await
+            .map_err(|e| CommandError::db("Failed to index file", e))?;
+        summary.files += 1;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    summary.total = summary.projects + summary.boards + summary.cards + summary.files;
+    log::info!("Rebuilt search index: {} entities", summary.total);
+    Ok(summary)
+}
+
+// ============================================
+// Search History
+// ============================================
+
+/// How many past searches are kept; older ones are dropped as new ones are recorded
+const MAX_SEARCH_HISTORY: i64 = 500;
+
+/// Most suggestions `get_search_suggestions` returns
+const MAX_SEARCH_SUGGESTIONS: i64 = 50;
+
+/// Remember a query and how many results it had, trimming the history to `MAX_SEARCH_HISTORY`
+async fn record_search(state: &AppState, query: &str, result_count: i64) -> Result<(), CommandError> {
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    sqlx::query("INSERT INTO search_history (query, ran_at, result_count) VALUES (?, ?, ?)")
+        .bind(query)
+        .bind(chrono::Utc::now().timestamp_millis())
+        .bind(result_count)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to record search", e))?;
+
+    sqlx::query(
+        "DELETE FROM search_history WHERE id NOT IN
+             (SELECT id FROM search_history ORDER BY ran_at DESC, id DESC LIMIT ?)",
+    )
+    .bind(MAX_SEARCH_HISTORY)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to trim search history", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))
+}
+
+/// Get past queries starting with `prefix`, most recently run first, each once
+/// Matching ignores ASCII case. `limit` defaults to 10 and is capped at `MAX_SEARCH_SUGGESTIONS`
+#[command]
+pub async fn get_search_suggestions(
+    state: State<'_, AppState>,
+    prefix: String,
+    limit: Option<i64>,
+) -> Result<Vec<String>, CommandError> {
+    // % and _ in the prefix match literally
+    let pattern = format!(
+        "{}%",
+        prefix.trim_start().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+    );
+
+    sqlx::query_scalar(
+        "SELECT query FROM search_history WHERE query LIKE ? ESCAPE '\\'
+         GROUP BY query ORDER BY MAX(ran_at) DESC LIMIT ?",
+    )
+    .bind(pattern)
+    .bind(limit.unwrap_or(10).clamp(0, MAX_SEARCH_SUGGESTIONS))
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get search suggestions", e))
+}
+
+/// Forget every recorded search
+/// Returns the number of entries removed
+#[command]
+pub async fn clear_search_history(state: State<'_, AppState>) -> Result<u64, CommandError> {
+    let cleared = sqlx::query("DELETE FROM search_history")
+        .execute(&state.writer())
+        .await
+        .map_err(|e| CommandError::db("Failed to clear search history", e))?
+        .rows_affected();
+
+    log::info!("Cleared search history ({} entries)", cleared);
+    Ok(cleared)
+}
+
+#[cfg(test)]
+mod tests {
+    use tauri::Manager;
+
+    use super::*;
+    use crate::commands::cards::update_card;
+    use crate::test_support::{seed_card, test_app};
+
+    /// Rows matching `query` in an in-memory FTS5 table laid out like `search_index`
+    fn fts_match(query: &str) -> rusqlite::Result<Vec<String>> {
+        let conn = rusqlite::Connection::open_in_memory()?;
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE search_index USING fts5(
+                 entity_id UNINDEXED, title, content, tokenize = 'porter unicode61'
+             );
+             INSERT INTO search_index VALUES ('1', 'C++ tips', 'templates and foo');
+             INSERT INTO search_index VALUES ('2', 'Quotes', 'say \"hello\" there');",
+        )?;
+        let mut statement = conn.prepare("SELECT entity_id FROM search_index WHERE search_index MATCH ?1 ORDER BY rank")?;
+        let rows = statement.query_map([query], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    #[test]
+    fn sanitize_fts_query_quotes_punctuation() {
+        assert_eq!(sanitize_fts_query("c++"), "\"c++\"");
+        assert_eq!(fts_match(&sanitize_fts_query("c++")).unwrap(), vec!["1"]);
+    }
+
+    #[test]
+    fn sanitize_fts_query_drops_dangling_operators() {
+        assert_eq!(sanitize_fts_query("foo AND"), "\"foo\"");
+        assert_eq!(sanitize_fts_query("NOT foo OR"), "\"foo\"");
+        assert_eq!(fts_match(&sanitize_fts_query("foo AND")).unwrap(), vec!["1"]);
+    }
+
+    #[test]
+    fn sanitize_fts_query_escapes_unbalanced_quotes() {
+        assert_eq!(sanitize_fts_query("\"hello"), "\"\"\"hello\"");
+        assert_eq!(sanitize_fts_query("say \"hello"), "\"say\" \"\"\"hello\"");
+        assert_eq!(fts_match(&sanitize_fts_query("\"hello")).unwrap(), vec!["2"]);
+        assert_eq!(fts_match(&sanitize_fts_query("say \"hello")).unwrap(), vec!["2"]);
+    }
+
+    #[test]
+    fn sanitize_fts_query_drops_punctuation_only_terms() {
+        assert_eq!(sanitize_fts_query("( foo ) -"), "\"foo\"");
+        assert!(fts_match(&sanitize_fts_query("(foo")).is_ok());
+        assert!(fts_match(&sanitize_fts_query("foo*) NEAR(")).is_ok());
+    }
+
+    #[tokio::test]
+    async fn blank_queries_return_nothing_without_the_database() {
+        use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+        // Pools that only connect on first use, so any query shows up in their size
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lazy.db");
+        let options = SqliteConnectOptions::new().filename(&path).create_if_missing(true);
+        let database = crate::db::Database {
+            pool: SqlitePoolOptions::new().connect_lazy_with(options.clone()),
+            writer: SqlitePoolOptions::new().max_connections(1).connect_lazy_with(options),
+        };
+        let app = tauri::test::mock_app();
+        app.manage(AppState::new(database, path));
+
+        for mode in ["exact", "prefix", "fuzzy"] {
+            let search = fts_search(
+                app.state(),
+                " \t ".into(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(mode.into()),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+            assert_eq!(search.total, 0);
+            assert!(search.results.is_empty());
+        }
+        let state = app.state::<AppState>();
+        assert_eq!(state.pool().size() + state.writer().size(), 0);
+    }
+
+    #[test]
+    fn unsanitized_input_fails_to_match() {
+        assert!(fts_match("foo AND").is_err());
+        assert!(fts_match("\"hello").is_err());
+    }
+
+    #[test]
+    fn edit_distance_counts_character_edits() {
+        let cases = [
+            ("garden", "garden", 0),
+            ("gardn", "garden", 1),
+            ("gardem", "garden", 1),
+            ("gardne", "garden", 2),
+            ("café", "cafe", 1),
+            ("", "abc", 3),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(edit_distance(a, b), expected, "{} / {}", a, b);
+        }
+    }
+
+    #[tokio::test]
+    async fn fuzzy_mode_finds_typos_without_spellfix() {
+        let (app, _dir) = test_app().await;
+        seed_card(&app, "c").await;
+        let content = r#"{"type":"doc","content":[{"type":"paragraph","content":[{"type":"text","text":"the garden shed"}]}]}"#;
+        update_card(app.state(), "c".into(), None, Some(content.into()), None, None, None)
+            .await
+            .unwrap();
+        assert!(!has_spellfix(&app.state::<AppState>().pool()).await);
+
+        let mut found = Vec::new();
+        for mode in ["prefix", "fuzzy"] {
+            let search = fts_search(
+                app.state(),
+                "gardn".into(),
+                Some(vec!["card".into()]),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(mode.into()),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+            found.push(search.total);
+        }
+        assert_eq!(found, vec![0, 1]);
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use tauri::{command, State};
+
+use super::boards::{Board, BOARD_COLUMNS};
+use super::cards::{Card, CARD_COLUMNS};
+use super::projects::{Project, PROJECT_COLUMNS};
+use crate::db::AppState;
+use crate::error::CommandError;
+
+// ============================================
+// Tombstones
+// ============================================
+
+/// A deleted project, board, card, tag or tag group
+/// Trashed cards have one too, until they are restored
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Tombstone {
+    pub entity_type: String,
+    pub entity_id: String,
+    pub deleted_at: i64,
+}
+
+/// Record that an entity was deleted, on the caller's transaction
+/// Deleting it again (e.g. purging a trashed card) moves `deleted_at` forward
+pub(crate) async fn record_tombstone(
+    conn: &mut SqliteConnection,
+    entity_type: &str,
+    entity_id: &str,
+    deleted_at: i64,
+) -> Result<(), CommandError> {
+    sqlx::query("INSERT OR REPLACE INTO tombstones (entity_type, entity_id, deleted_at) VALUES (?, ?, ?)")
+        .bind(entity_type)
+        .bind(entity_id)
+        .bind(deleted_at)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to record deletion", e))?;
+    Ok(())
+}
+
+/// Forget an entity's tombstone because it was brought back
+pub(crate) async fn clear_tombstone(
+    conn: &mut SqliteConnection,
+    entity_type: &str,
+    entity_id: &str,
+) -> Result<(), CommandError> {
+    sqlx::query("DELETE FROM tombstones WHERE entity_type = ? AND entity_id = ?")
+        .bind(entity_type)
+        .bind(entity_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| CommandError::db("Failed to clear deletion", e))?;
+    Ok(())
+}
+
+async fn fetch_tombstones(conn: &mut SqliteConnection, timestamp_ms: i64) -> Result<Vec<Tombstone>, CommandError> {
+    sqlx::query_as::<_, Tombstone>(
+        "SELECT entity_type, entity_id, deleted_at FROM tombstones WHERE deleted_at > ? ORDER BY deleted_at ASC",
+    )
+    .bind(timestamp_ms)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| CommandError::db("Failed to get deletions", e))
+}
+
+/// Get the deletions recorded after `timestamp_ms`, oldest first
+#[command]
+pub async fn get_tombstones_since(state: State<'_, AppState>, timestamp_ms: i64) -> Result<Vec<Tombstone>, CommandError> {
+    let mut conn = state.pool()
+        .acquire()
+        .await
+        .map_err(|e| CommandError::db("Failed to get a connection", e))?;
+    fetch_tombstones(&mut conn, timestamp_ms).await
+}
+
+/// Forget deletions recorded at least `older_than_ms` ago
+/// A client that last synced before then has to do a full reload instead.
+/// Returns the number of tombstones removed
+#[command]
+pub async fn purge_tombstones(state: State<'_, AppState>, older_than_ms: i64) -> Result<u64, CommandError> {
+    let cutoff = chrono::Utc::now().timestamp_millis() - older_than_ms;
+
+    let purged = sqlx::query("DELETE FROM tombstones WHERE deleted_at <= ?")
+        .bind(cutoff)
+        .execute(&state.writer())
+        .await
+        .map_err(|e| CommandError::db("Failed to purge tombstones", e))?
+        .rows_affected();
+
+    if purged > 0 {
+        log::info!("Purged {} tombstones", purged);
+    }
+    Ok(purged)
+}
+
+// ============================================
+// Sync Commands
+// ============================================
+
+/// Everything that changed after a point in time
+/// `cards` includes trashed cards (their `deletedAt` is set), which also have a
+/// tombstone in `deleted`. Pass `until` as the next call's timestamp
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSet {
+    pub projects: Vec<Project>,
+    pub boards: Vec<Board>,
+    pub cards: Vec<Card>,
+    pub deleted: Vec<Tombstone>,
+    pub until: i64,
+}
+
+/// Get the projects, boards and cards updated after `timestamp_ms`, and the ids
+/// deleted since then
+#[command]
+pub async fn get_changes_since(state: State<'_, AppState>, timestamp_ms: i64) -> Result<ChangeSet, CommandError> {
+    let until = chrono::Utc::now().timestamp_millis();
+
+    // One read transaction, so all four lists come from the same snapshot
+    let mut tx = state.pool()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let projects = sqlx::query_as::<_, Project>(&format!(
+        "SELECT {} FROM projects WHERE updated_at > ? ORDER BY updated_at ASC",
+        PROJECT_COLUMNS
+    ))
+    .bind(timestamp_ms)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to get changed projects", e))?;
+
+    let boards = sqlx::query_as::<_, Board>(&format!(
+        "SELECT {} FROM boards WHERE updated_at > ? ORDER BY updated_at ASC",
+        BOARD_COLUMNS
+    ))
+    .bind(timestamp_ms)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to get changed boards", e))?;
+
+    let cards = sqlx::query_as::<_, Card>(&format!(
+        "SELECT {} FROM cards WHERE updated_at > ? ORDER BY updated_at ASC",
+        CARD_COLUMNS
+    ))
+    .bind(timestamp_ms)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to get changed cards", e))?;
+
+    let deleted = fetch_tombstones(&mut tx, timestamp_ms).await?;
+
+    Ok(ChangeSet { projects, boards, cards, deleted, until })
+}
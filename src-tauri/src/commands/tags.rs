@@ -0,0 +1,476 @@
+use serde::{Deserialize, Serialize};
+use tauri::{command, State};
+
+use super::sync;
+use crate::db::AppState;
+use crate::error::CommandError;
+use crate::validate;
+
+// ============================================
+// Tag Commands
+// ============================================
+
+pub(crate) const TAG_COLUMNS: &str = "id, name, color, group_id, position, created_at";
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    pub color: Option<String>,
+    pub group_id: Option<String>,
+    pub position: i64,
+    pub created_at: i64,
+}
+
+/// Create a new tag; tag names are unique
+#[command]
+pub async fn create_tag(
+    state: State<'_, AppState>,
+    id: String,
+    name: String,
+    color: Option<String>,
+    group_id: Option<String>,
+    position: Option<i64>,
+) -> Result<Tag, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let name = validate::title("name", &name)?;
+    let color = color.map(|color| validate::hex_color("color", &color)).transpose()?;
+
+    let tag = sqlx::query_as::<_, Tag>(&format!(
+        "INSERT INTO tags ({0}) VALUES (?, ?, ?, ?, ?, ?) RETURNING {0}",
+        TAG_COLUMNS
+    ))
+    .bind(&id)
+    .bind(&name)
+    .bind(&color)
+    .bind(&group_id)
+    .bind(position.unwrap_or(0))
+    .bind(now)
+    .fetch_one(&state.writer())
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+            CommandError::Conflict(format!("tag already exists: {}", name))
+        }
+        _ => CommandError::db("Failed to create tag", e),
+    })?;
+
+    log::info!("Created tag: {}", tag.id);
+    Ok(tag)
+}
+
+/// A tag group with its tags, for `get_tags` with `nested`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagGroupWithTags {
+    #[serde(flatten)]
+    pub group: TagGroup,
+    pub tags: Vec<Tag>,
+}
+
+/// Result of `get_tags`: a flat list, or tags nested under their groups
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TagList {
+    Flat(Vec<Tag>),
+    Nested {
+        groups: Vec<TagGroupWithTags>,
+        /// Tags with no group, or whose group no longer exists
+        ungrouped: Vec<Tag>,
+    },
+}
+
+/// Get tags ordered by position then name, optionally only those of one group
+/// With `nested` the tags come grouped: every group (ordered like `get_tag_groups`)
+/// with its tags, plus the tags that belong to no group
+#[command]
+pub async fn get_tags(
+    state: State<'_, AppState>,
+    group_id: Option<String>,
+    nested: Option<bool>,
+) -> Result<TagList, CommandError> {
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new(format!("SELECT {} FROM tags", TAG_COLUMNS));
+    if let Some(group_id) = &group_id {
+        query.push(" WHERE group_id = ").push_bind(group_id);
+    }
+    query.push(" ORDER BY position ASC, name ASC");
+
+    let tags = query
+        .build_query_as::<Tag>()
+        .fetch_all(&state.pool())
+        .await
+        .map_err(|e| CommandError::db("Failed to get tags", e))?;
+
+    if !nested.unwrap_or(false) {
+        return Ok(TagList::Flat(tags));
+    }
+
+    let mut groups: Vec<TagGroupWithTags> = fetch_tag_groups(&state)
+        .await?
+        .into_iter()
+        .filter(|group| group_id.as_ref().map_or(true, |id| id == &group.id))
+        .map(|group| TagGroupWithTags { group, tags: Vec::new() })
+        .collect();
+    let mut ungrouped = Vec::new();
+    for tag in tags {
+        let group = groups
+            .iter_mut()
+            .find(|group| tag.group_id.as_ref() == Some(&group.group.id));
+        match group {
+            Some(group) => group.tags.push(tag),
+            None => ungrouped.push(tag),
+        }
+    }
+
+    Ok(TagList::Nested { groups, ungrouped })
+}
+
+/// Update a tag, touching only the fields that were provided
+#[command]
+pub async fn update_tag(
+    state: State<'_, AppState>,
+    id: String,
+    name: Option<String>,
+    color: Option<String>,
+    group_id: Option<String>,
+    position: Option<i64>,
+) -> Result<Tag, CommandError> {
+    // Tags have no updated_at, so start from a no-op assignment to keep the SET clause valid
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new("UPDATE tags SET id = id");
+    if let Some(name) = name {
+        query.push(", name = ").push_bind(validate::title("name", &name)?);
+    }
+    if let Some(color) = color {
+        query.push(", color = ").push_bind(validate::hex_color("color", &color)?);
+    }
+    if let Some(group_id) = group_id {
+        query.push(", group_id = ").push_bind(group_id);
+    }
+    if let Some(position) = position {
+        query.push(", position = ").push_bind(position);
+    }
+    query.push(" WHERE id = ").push_bind(&id);
+    query.push(format!(" RETURNING {}", TAG_COLUMNS));
+
+    let tag = query
+        .build_query_as::<Tag>()
+        .fetch_optional(&state.writer())
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+                CommandError::Conflict("tag already exists".to_string())
+            }
+            _ => CommandError::db("Failed to update tag", e),
+        })?
+        .ok_or_else(|| CommandError::NotFound("tag not found".to_string()))?;
+
+    log::info!("Updated tag: {}", tag.id);
+    Ok(tag)
+}
+
+/// Delete a tag and detach it from all cards
+/// Returns false if no tag with this id existed
+#[command]
+pub async fn delete_tag(state: State<'_, AppState>, id: String) -> Result<bool, CommandError> {
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    sqlx::query("DELETE FROM card_tags WHERE tag_id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to detach tag", e))?;
+
+    let deleted = sqlx::query("DELETE FROM tags WHERE id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to delete tag", e))?
+        .rows_affected()
+        > 0;
+    if deleted {
+        sync::record_tombstone(&mut tx, "tag", &id, chrono::Utc::now().timestamp_millis()).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    if deleted {
+        log::info!("Deleted tag: {}", id);
+    }
+    Ok(deleted)
+}
+
+/// Merge the tag `source_id` into `target_id`: every card and entity tagged with the
+/// source gets the target instead (once, if it had both) and the source tag is deleted.
+/// Returns the number of cards that carried the source tag
+#[command]
+pub async fn merge_tags(state: State<'_, AppState>, source_id: String, target_id: String) -> Result<i64, CommandError> {
+    if source_id == target_id {
+        return Err(CommandError::Validation("cannot merge a tag into itself".to_string()));
+    }
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    for id in [&source_id, &target_id] {
+        let exists = sqlx::query("SELECT 1 FROM tags WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to look up tag", e))?
+            .is_some();
+        if !exists {
+            return Err(CommandError::NotFound(format!("tag not found: {}", id)));
+        }
+    }
+
+    let card_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM card_tags WHERE tag_id = ?")
+        .bind(&source_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to count tagged cards", e))?;
+
+    sqlx::query(
+        "INSERT OR IGNORE INTO card_tags (card_id, tag_id, created_at)
+         SELECT card_id, ?, created_at FROM card_tags WHERE tag_id = ?",
+    )
+    .bind(&target_id)
+    .bind(&source_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to retag cards", e))?;
+
+    sqlx::query(
+        "INSERT OR IGNORE INTO tag_relations (tag_id, entity_type, entity_id, created_at)
+         SELECT ?, entity_type, entity_id, created_at FROM tag_relations WHERE tag_id = ?",
+    )
+    .bind(&target_id)
+    .bind(&source_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to retag entities", e))?;
+
+    for table in ["card_tags", "tag_relations"] {
+        sqlx::query(&format!("DELETE FROM {} WHERE tag_id = ?", table))
+            .bind(&source_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to detach tag", e))?;
+    }
+
+    sqlx::query("DELETE FROM tags WHERE id = ?")
+        .bind(&source_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to delete tag", e))?;
+    sync::record_tombstone(&mut tx, "tag", &source_id, chrono::Utc::now().timestamp_millis()).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Merged tag {} into {} ({} cards)", source_id, target_id, card_count);
+    Ok(card_count)
+}
+
+// ============================================
+// Tag Group Commands
+// ============================================
+
+pub(crate) const TAG_GROUP_COLUMNS: &str = "id, name, color, position, created_at";
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TagGroup {
+    pub id: String,
+    pub name: String,
+    pub color: Option<String>,
+    pub position: i64,
+    pub created_at: i64,
+}
+
+/// Create a new tag group; group names are unique
+/// When no position is given the group is appended after the others
+#[command]
+pub async fn create_tag_group(
+    state: State<'_, AppState>,
+    id: String,
+    name: String,
+    color: Option<String>,
+    position: Option<i64>,
+) -> Result<TagGroup, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let name = validate::title("name", &name)?;
+    let color = color.map(|color| validate::hex_color("color", &color)).transpose()?;
+
+    let group = sqlx::query_as::<_, TagGroup>(&format!(
+        "INSERT INTO tag_groups ({0})
+         VALUES (?, ?, ?, COALESCE(?, (SELECT COALESCE(MAX(position) + 1, 0) FROM tag_groups)), ?)
+         RETURNING {0}",
+        TAG_GROUP_COLUMNS
+    ))
+    .bind(&id)
+    .bind(&name)
+    .bind(&color)
+    .bind(position)
+    .bind(now)
+    .fetch_one(&state.writer())
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+            CommandError::Conflict(format!("tag group already exists: {}", name))
+        }
+        _ => CommandError::db("Failed to create tag group", e),
+    })?;
+
+    log::info!("Created tag group: {}", group.id);
+    Ok(group)
+}
+
+async fn fetch_tag_groups(state: &AppState) -> Result<Vec<TagGroup>, CommandError> {
+    sqlx::query_as::<_, TagGroup>(&format!(
+        "SELECT {} FROM tag_groups ORDER BY position ASC, name ASC",
+        TAG_GROUP_COLUMNS
+    ))
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get tag groups", e))
+}
+
+/// Get all tag groups ordered by position then name
+#[command]
+pub async fn get_tag_groups(state: State<'_, AppState>) -> Result<Vec<TagGroup>, CommandError> {
+    fetch_tag_groups(&state).await
+}
+
+/// Update a tag group, touching only the fields that were provided
+#[command]
+pub async fn update_tag_group(
+    state: State<'_, AppState>,
+    id: String,
+    name: Option<String>,
+    color: Option<String>,
+    position: Option<i64>,
+) -> Result<TagGroup, CommandError> {
+    // Groups have no updated_at either, see `update_tag`
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new("UPDATE tag_groups SET id = id");
+    if let Some(name) = name {
+        query.push(", name = ").push_bind(validate::title("name", &name)?);
+    }
+    if let Some(color) = color {
+        query.push(", color = ").push_bind(validate::hex_color("color", &color)?);
+    }
+    if let Some(position) = position {
+        query.push(", position = ").push_bind(position);
+    }
+    query.push(" WHERE id = ").push_bind(&id);
+    query.push(format!(" RETURNING {}", TAG_GROUP_COLUMNS));
+
+    let group = query
+        .build_query_as::<TagGroup>()
+        .fetch_optional(&state.writer())
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+                CommandError::Conflict("tag group already exists".to_string())
+            }
+            _ => CommandError::db("Failed to update tag group", e),
+        })?
+        .ok_or_else(|| CommandError::NotFound("tag group not found".to_string()))?;
+
+    log::info!("Updated tag group: {}", group.id);
+    Ok(group)
+}
+
+/// Delete a tag group; its tags are kept and become ungrouped
+/// Returns false if no group with this id existed
+#[command]
+pub async fn delete_tag_group(state: State<'_, AppState>, id: String) -> Result<bool, CommandError> {
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    sqlx::query("UPDATE tags SET group_id = NULL WHERE group_id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to ungroup tags", e))?;
+
+    let deleted = sqlx::query("DELETE FROM tag_groups WHERE id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to delete tag group", e))?
+        .rows_affected()
+        > 0;
+    if deleted {
+        sync::record_tombstone(&mut tx, "tag_group", &id, chrono::Utc::now().timestamp_millis()).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    if deleted {
+        log::info!("Deleted tag group: {}", id);
+    }
+    Ok(deleted)
+}
+
+// ============================================
+// Card Tag Commands
+// ============================================
+
+/// Attach a tag to a card (no-op if already attached)
+/// Like any change to its tags, this moves the card's `updated_at` forward
+#[command]
+pub async fn add_tag_to_card(
+    state: State<'_, AppState>,
+    card_id: String,
+    tag_id: String,
+) -> Result<(), CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+
+    sqlx::query("INSERT OR IGNORE INTO card_tags (card_id, tag_id, created_at) VALUES (?, ?, ?)")
+        .bind(&card_id)
+        .bind(&tag_id)
+        .bind(now)
+        .execute(&state.writer())
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err) if db_err.is_foreign_key_violation() => {
+                CommandError::NotFound("card or tag not found".to_string())
+            }
+            _ => CommandError::db("Failed to tag card", e),
+        })?;
+
+    Ok(())
+}
+
+/// Detach a tag from a card, moving its `updated_at` forward
+/// Returns false if the card didn't have the tag
+#[command]
+pub async fn remove_tag_from_card(
+    state: State<'_, AppState>,
+    card_id: String,
+    tag_id: String,
+) -> Result<bool, CommandError> {
+    let removed = sqlx::query("DELETE FROM card_tags WHERE card_id = ? AND tag_id = ?")
+        .bind(&card_id)
+        .bind(&tag_id)
+        .execute(&state.writer())
+        .await
+        .map_err(|e| CommandError::db("Failed to untag card", e))?
+        .rows_affected()
+        > 0;
+
+    Ok(removed)
+}
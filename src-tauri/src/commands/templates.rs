@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::{command, State};
+
+use super::boards::{compress_snapshot, decompress_snapshot, write_pending_snapshots, Board, BOARD_COLUMNS};
+use super::cards::{insert_card, NewCard};
+use super::search;
+use crate::db::AppState;
+use crate::error::CommandError;
+use crate::validate;
+
+// ============================================
+// Board Template Commands
+// ============================================
+
+/// A saved board template; its snapshot and cards are only read when instantiating
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardTemplate {
+    pub id: String,
+    pub name: String,
+    pub card_count: i64,
+    pub created_at: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct TemplateContent {
+    source_board_id: String,
+    tldraw_snapshot: Option<Vec<u8>>,
+    cards: String,
+}
+
+/// Save a board's canvas snapshot and cards (with their placements) as a template
+/// Templates are global, so any project can use them. Child boards and tags are not
+/// part of a template
+#[command]
+pub async fn save_board_as_template(
+    state: State<'_, AppState>,
+    board_id: String,
+    name: String,
+) -> Result<BoardTemplate, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let name = validate::title("name", &name)?;
+
+    // A snapshot still waiting in the autosave buffer is the current canvas
+    write_pending_snapshots(&state, Some(&board_id)).await?;
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let snapshot: Option<Vec<u8>> = sqlx::query_scalar("SELECT tldraw_snapshot FROM boards WHERE id = ?")
+        .bind(&board_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to get board", e))?
+        .ok_or_else(|| CommandError::NotFound(format!("board not found: {}", board_id)))?;
+
+    let cards = sqlx::query_as::<_, NewCard>(
+        "SELECT c.id, c.title, c.content, c.content_type, c.color, c.metadata, bc.x, bc.y, bc.width, bc.height
+         FROM board_cards bc JOIN cards c ON c.id = bc.card_id
+         WHERE bc.board_id = ? AND c.deleted_at IS NULL
+         ORDER BY bc.created_at ASC",
+    )
+    .bind(&board_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to get board cards", e))?;
+    let cards_json = serde_json::to_string(&cards)
+        .map_err(|e| CommandError::internal("Failed to encode template cards", e))?;
+
+    let template = sqlx::query_as::<_, BoardTemplate>(
+        "INSERT INTO board_templates (id, name, source_board_id, tldraw_snapshot, cards, card_count, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)
+         RETURNING id, name, card_count, created_at",
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(&name)
+    .bind(&board_id)
+    .bind(snapshot)
+    .bind(&cards_json)
+    .bind(cards.len() as i64)
+    .bind(now)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to save template", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Saved board {} as template {} ({} cards)", board_id, template.id, cards.len());
+    Ok(template)
+}
+
+/// Get all board templates, newest first
+#[command]
+pub async fn get_board_templates(state: State<'_, AppState>) -> Result<Vec<BoardTemplate>, CommandError> {
+    sqlx::query_as::<_, BoardTemplate>(
+        "SELECT id, name, card_count, created_at FROM board_templates ORDER BY created_at DESC",
+    )
+    .fetch_all(&state.pool())
+    .await
+    .map_err(|e| CommandError::db("Failed to get templates", e))
+}
+
+/// Create a top-level board in `project_id` from a template
+/// The board and its cards get fresh ids, and the ids inside the snapshot are remapped
+/// to them. The board is appended after the project's other top-level boards
+#[command]
+pub async fn create_board_from_template(
+    state: State<'_, AppState>,
+    project_id: String,
+    template_id: String,
+    title: String,
+) -> Result<Board, CommandError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let title = validate::title("title", &title)?;
+    let new_id = || uuid::Uuid::new_v4().to_string();
+
+    let mut tx = state.writer()
+        .begin()
+        .await
+        .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+    let template = sqlx::query_as::<_, TemplateContent>(
+        "SELECT source_board_id, tldraw_snapshot, cards FROM board_templates WHERE id = ?",
+    )
+    .bind(&template_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to get template", e))?
+    .ok_or_else(|| CommandError::NotFound(format!("template not found: {}", template_id)))?;
+    let cards: Vec<NewCard> = serde_json::from_str(&template.cards)
+        .map_err(|e| CommandError::internal("Failed to read template cards", e))?;
+
+    let project_exists = sqlx::query("SELECT 1 FROM projects WHERE id = ?")
+        .bind(&project_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| CommandError::db("Failed to look up project", e))?
+        .is_some();
+    if !project_exists {
+        return Err(CommandError::NotFound(format!("project not found: {}", project_id)));
+    }
+
+    let board_id = new_id();
+    let ids: HashMap<String, String> = std::iter::once((template.source_board_id.clone(), board_id.clone()))
+        .chain(cards.iter().map(|card| (card.id.clone(), new_id())))
+        .collect();
+
+    // Shapes refer to boards and cards by quoted id, as in `duplicate_board`
+    let snapshot = template
+        .tldraw_snapshot
+        .as_deref()
+        .map(|data| {
+            decompress_snapshot(data).and_then(|snapshot| {
+                let mut snapshot = snapshot;
+                for (old, new) in &ids {
+                    snapshot = snapshot.replace(&format!("\"{}\"", old), &format!("\"{}\"", new));
+                }
+                compress_snapshot(&snapshot)
+            })
+        })
+        .transpose()?;
+
+    let board = sqlx::query_as::<_, Board>(&format!(
+        "INSERT INTO boards (id, project_id, parent_board_id, title, position, tldraw_snapshot,
+             snapshot_updated_at, created_at, updated_at)
+         VALUES (?, ?, NULL, ?,
+             (SELECT COALESCE(MAX(position) + 1, 0) FROM boards WHERE project_id = ? AND parent_board_id IS NULL),
+             ?, ?, ?, ?)
+         RETURNING {}",
+        BOARD_COLUMNS
+    ))
+    .bind(&board_id)
+    .bind(&project_id)
+    .bind(&title)
+    .bind(&project_id)
+    .bind(&snapshot)
+    .bind(snapshot.as_ref().map(|_| now))
+    .bind(now)
+    .bind(now)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| CommandError::db("Failed to create board", e))?;
+    search::index_entity(&mut tx, "board", &board.id, &board.title, "", "")
+        .await
+        .map_err(|e| CommandError::db("Failed to index board", e))?;
+
+    let card_count = cards.len();
+    for card in cards {
+        let id = ids[&card.id].clone();
+        insert_card(&mut tx, &board.id, NewCard { id, ..card }).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| CommandError::db("Failed to commit transaction", e))?;
+
+    log::info!("Created board {} from template {} ({} cards)", board.id, template_id, card_count);
+    Ok(board)
+}
+
+/// Delete a board template; boards created from it are not affected
+/// Returns false if no template with this id existed
+#[command]
+pub async fn delete_board_template(state: State<'_, AppState>, template_id: String) -> Result<bool, CommandError> {
+    let deleted = sqlx::query("DELETE FROM board_templates WHERE id = ?")
+        .bind(&template_id)
+        .execute(&state.writer())
+        .await
+        .map_err(|e| CommandError::db("Failed to delete template", e))?
+        .rows_affected()
+        > 0;
+
+    if deleted {
+        log::info!("Deleted board template: {}", template_id);
+    }
+    Ok(deleted)
+}
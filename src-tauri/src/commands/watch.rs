@@ -0,0 +1,133 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant, SystemTime};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use tauri::{command, Emitter, Manager, State};
+
+use crate::db::{self, AppState};
+use crate::error::CommandError;
+
+// ============================================
+// Database Watch Commands
+// ============================================
+
+/// Event emitted when another process changed the database file
+pub const DB_CHANGED_EVENT: &str = "db-changed";
+
+/// Quiet period after the last file event before a change is reported
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Longest wait for one of our own commits to be recorded before a change is checked
+const LOCAL_WRITE_WAIT: Duration = Duration::from_secs(5);
+
+/// Latest modification time of the database file and its WAL
+pub(crate) fn db_mtime(db_path: &Path) -> Option<SystemTime> {
+    let mut wal = db_path.as_os_str().to_owned();
+    wal.push("-wal");
+    [db_path.to_path_buf(), PathBuf::from(wal)]
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .max()
+}
+
+/// Whether a watcher event is a content change to the database or its WAL
+fn touches_db(event: &notify::Event, file_name: &str) -> bool {
+    let wal_name = format!("{}-wal", file_name);
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+        && event.paths.iter().any(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name == file_name || name == wal_name)
+        })
+}
+
+/// Reopen the pools so connections see a file that was replaced rather than edited
+fn reopen(app: &tauri::AppHandle, db_path: &Path) -> Result<(), CommandError> {
+    let db = tauri::async_runtime::block_on(db::connect(db_path))?;
+    let previous = app.state::<AppState>().replace(db, db_path.to_path_buf());
+    tauri::async_runtime::spawn(async move { previous.close().await });
+    Ok(())
+}
+
+/// Turn raw watcher events into debounced `db-changed` events
+/// Ends when the watcher is dropped or a different database is opened
+fn watch_loop(app: tauri::AppHandle, events: Receiver<notify::Event>, db_path: PathBuf) {
+    let file_name = db_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mut last_seen = db_mtime(&db_path);
+
+    while let Ok(event) = events.recv() {
+        if !touches_db(&event, &file_name) {
+            continue;
+        }
+        // Wait for the burst of events (sync clients write in chunks) to settle
+        loop {
+            match events.recv_timeout(DEBOUNCE) {
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+        if app.state::<AppState>().path() != db_path {
+            return;
+        }
+
+        // A commit of ours that is still finishing has no recorded file time yet
+        let deadline = Instant::now() + LOCAL_WRITE_WAIT;
+        while db::local_write_pending() && Instant::now() < deadline {
+            std::thread::sleep(DEBOUNCE / 10);
+        }
+
+        let mtime = db_mtime(&db_path);
+        if mtime == last_seen {
+            continue;
+        }
+        last_seen = mtime;
+        // The files are as our last write left them, so nobody else wrote since
+        if mtime.is_some() && mtime == db::local_write_mtime() {
+            continue;
+        }
+
+        log::info!("Database changed externally: {:?}", db_path);
+        if let Err(e) = reopen(&app, &db_path) {
+            log::warn!("Failed to reopen database after external change: {}", e);
+        }
+        if let Err(e) = app.emit(DB_CHANGED_EVENT, db_path.to_string_lossy().to_string()) {
+            log::warn!("Failed to emit {}: {}", DB_CHANGED_EVENT, e);
+        }
+    }
+}
+
+/// Watch the open database file and emit `db-changed` (with its path) when another
+/// process, such as a sync client, modifies it. Our own writes are ignored, bursts
+/// are debounced and the pool is reopened before the event so refetches see the
+/// new data. Calling it again restarts the watch on the currently open database
+#[command]
+pub async fn start_db_watch(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), CommandError> {
+    let db_path = state.path();
+    let dir = db_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let (sender, events) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            let _ = sender.send(event);
+        }
+    })
+    .map_err(|e| CommandError::internal("Failed to start database watch", e))?;
+    // The folder is watched rather than the file, since sync clients often replace it
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| CommandError::internal("Failed to start database watch", e))?;
+
+    let watched = db_path.clone();
+    std::thread::spawn(move || watch_loop(app, events, watched));
+    // Dropping the previous watcher ends its loop
+    state.set_watcher(watcher);
+
+    log::info!("Watching database for external changes: {:?}", db_path);
+    Ok(())
+}
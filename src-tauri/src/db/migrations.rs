@@ -0,0 +1,317 @@
+use sqlx::{Executor, SqlitePool};
+
+use crate::error::CommandError;
+
+// ============================================
+// Schema Migrations
+// ============================================
+
+/// One step in the schema history; `version` is stored in `PRAGMA user_version`
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+/// All migrations in order. Append new ones here, never edit an applied migration
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "core schema",
+        sql: include_str!("schema.sql"),
+    },
+    Migration {
+        version: 2,
+        description: "board card size",
+        sql: "ALTER TABLE board_cards ADD COLUMN width REAL;
+              ALTER TABLE board_cards ADD COLUMN height REAL;",
+    },
+    Migration {
+        version: 3,
+        description: "asset text search",
+        sql: "ALTER TABLE assets ADD COLUMN extracted_text TEXT;
+              CREATE TRIGGER IF NOT EXISTS assets_search_cleanup AFTER DELETE ON assets BEGIN
+                  DELETE FROM search_index WHERE entity_type = 'file' AND entity_id = old.id;
+              END;",
+    },
+    Migration {
+        version: 4,
+        description: "card revisions",
+        sql: "CREATE TABLE IF NOT EXISTS card_revisions (
+                  id TEXT PRIMARY KEY,
+                  card_id TEXT NOT NULL,
+                  content TEXT,
+                  word_count INTEGER DEFAULT 0,
+                  saved_at INTEGER NOT NULL,
+                  FOREIGN KEY (card_id) REFERENCES cards(id) ON DELETE CASCADE
+              );
+              CREATE INDEX IF NOT EXISTS idx_card_revisions_card ON card_revisions(card_id, saved_at);",
+    },
+    Migration {
+        version: 5,
+        description: "card previews",
+        sql: "ALTER TABLE cards ADD COLUMN preview TEXT;",
+    },
+    Migration {
+        version: 6,
+        description: "search vocabulary",
+        sql: "CREATE VIRTUAL TABLE IF NOT EXISTS search_vocab USING fts5vocab(search_index, row);",
+    },
+    Migration {
+        version: 7,
+        description: "tag groups",
+        sql: "CREATE TABLE IF NOT EXISTS tag_groups (
+                  id TEXT PRIMARY KEY,
+                  name TEXT NOT NULL UNIQUE,
+                  color TEXT,
+                  position INTEGER NOT NULL DEFAULT 0,
+                  created_at INTEGER NOT NULL
+              );
+              CREATE INDEX IF NOT EXISTS idx_tags_group ON tags(group_id);",
+    },
+    Migration {
+        version: 8,
+        description: "board templates",
+        sql: "CREATE TABLE IF NOT EXISTS board_templates (
+                  id TEXT PRIMARY KEY,
+                  name TEXT NOT NULL,
+                  source_board_id TEXT NOT NULL, -- ids in the snapshot refer to this board
+                  tldraw_snapshot BLOB, -- stored like boards.tldraw_snapshot
+                  cards TEXT NOT NULL, -- JSON array of cards with their placements
+                  card_count INTEGER NOT NULL DEFAULT 0,
+                  created_at INTEGER NOT NULL
+              );",
+    },
+    Migration {
+        version: 9,
+        description: "tombstones",
+        sql: "CREATE TABLE IF NOT EXISTS tombstones (
+                  entity_type TEXT NOT NULL,
+                  entity_id TEXT NOT NULL,
+                  deleted_at INTEGER NOT NULL,
+                  PRIMARY KEY (entity_type, entity_id)
+              );
+              CREATE INDEX IF NOT EXISTS idx_tombstones_deleted ON tombstones(deleted_at);",
+    },
+    Migration {
+        version: 10,
+        description: "app state",
+        sql: "CREATE TABLE IF NOT EXISTS app_state (
+                  key TEXT PRIMARY KEY,
+                  value TEXT NOT NULL,
+                  updated_at INTEGER NOT NULL
+              );",
+    },
+    Migration {
+        version: 11,
+        description: "search history",
+        sql: "CREATE TABLE IF NOT EXISTS search_history (
+                  id INTEGER PRIMARY KEY AUTOINCREMENT,
+                  query TEXT NOT NULL, -- as typed, before sanitizing
+                  ran_at INTEGER NOT NULL,
+                  result_count INTEGER NOT NULL
+              );
+              CREATE INDEX IF NOT EXISTS idx_search_history_ran ON search_history(ran_at);",
+    },
+    Migration {
+        version: 12,
+        description: "asset references",
+        sql: "CREATE TABLE IF NOT EXISTS asset_refs (
+                  card_id TEXT NOT NULL,
+                  relative_path TEXT NOT NULL,
+                  PRIMARY KEY (card_id, relative_path),
+                  FOREIGN KEY (card_id) REFERENCES cards(id) ON DELETE CASCADE,
+                  FOREIGN KEY (relative_path) REFERENCES assets(relative_path) ON DELETE CASCADE ON UPDATE CASCADE
+              );
+              CREATE INDEX IF NOT EXISTS idx_asset_refs_path ON asset_refs(relative_path);
+              -- Existing cards, matched on the raw file name; saving a card refreshes its rows
+              INSERT OR IGNORE INTO asset_refs (card_id, relative_path)
+              SELECT c.id, a.relative_path FROM cards c JOIN assets a
+                  ON instr(c.content, substr(a.relative_path, length(rtrim(a.relative_path, replace(a.relative_path, '/', ''))) + 1)) > 0
+                  OR instr(c.metadata, substr(a.relative_path, length(rtrim(a.relative_path, replace(a.relative_path, '/', ''))) + 1)) > 0;",
+    },
+    Migration {
+        version: 13,
+        description: "pinned projects and cards",
+        sql: "ALTER TABLE projects ADD COLUMN is_pinned INTEGER NOT NULL DEFAULT 0;
+              ALTER TABLE cards ADD COLUMN is_pinned INTEGER NOT NULL DEFAULT 0;",
+    },
+    Migration {
+        version: 14,
+        description: "updated_at triggers",
+        // Commands set updated_at themselves; these catch any update that doesn't.
+        // Tagging or untagging a card counts as a change to the card; copies and imports
+        // put their own timestamp back afterwards (see `keep_card_updated_at`)
+        sql: "CREATE TRIGGER IF NOT EXISTS projects_touch AFTER UPDATE ON projects
+                  WHEN NEW.updated_at IS OLD.updated_at
+                   AND CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER) > OLD.updated_at
+              BEGIN
+                  UPDATE projects SET updated_at = CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+                  WHERE id = NEW.id;
+              END;
+              CREATE TRIGGER IF NOT EXISTS boards_touch AFTER UPDATE ON boards
+                  WHEN NEW.updated_at IS OLD.updated_at
+                   AND CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER) > OLD.updated_at
+              BEGIN
+                  UPDATE boards SET updated_at = CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+                  WHERE id = NEW.id;
+              END;
+              CREATE TRIGGER IF NOT EXISTS cards_touch AFTER UPDATE ON cards
+                  WHEN NEW.updated_at IS OLD.updated_at
+                   AND CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER) > OLD.updated_at
+              BEGIN
+                  UPDATE cards SET updated_at = CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+                  WHERE id = NEW.id;
+              END;
+              CREATE TRIGGER IF NOT EXISTS card_tags_touch_insert AFTER INSERT ON card_tags
+              BEGIN
+                  UPDATE cards SET updated_at = MAX(updated_at, CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER))
+                  WHERE id = NEW.card_id;
+              END;
+              CREATE TRIGGER IF NOT EXISTS card_tags_touch_delete AFTER DELETE ON card_tags
+              BEGIN
+                  UPDATE cards SET updated_at = MAX(updated_at, CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER))
+                  WHERE id = OLD.card_id;
+              END;",
+    },
+    Migration {
+        version: 15,
+        description: "asset references by full path",
+        // Migration 12 matched on the bare file name, so `a.png` also matched `data.png`.
+        // The full path is matched raw or with `/` and spaces percent-encoded as in asset
+        // URLs; saving a card then refreshes its rows with `refresh_asset_refs`
+        sql: "DELETE FROM asset_refs;
+              INSERT OR IGNORE INTO asset_refs (card_id, relative_path)
+              SELECT c.id, a.relative_path FROM cards c JOIN assets a
+                  ON instr(c.content, a.relative_path) > 0
+                  OR instr(c.metadata, a.relative_path) > 0
+                  OR instr(c.content, replace(replace(a.relative_path, '/', '%2F'), ' ', '%20')) > 0
+                  OR instr(c.metadata, replace(replace(a.relative_path, '/', '%2F'), ' ', '%20')) > 0;",
+    },
+];
+
+/// Schema version this build of the app expects
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// Apply every migration newer than the database's `user_version`, each in its own transaction
+/// Fails without changing anything if the database comes from a newer app version
+pub async fn run_migrations(pool: &SqlitePool) -> Result<(), CommandError> {
+    let current: i64 = sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| CommandError::db("Failed to read schema version", e))?;
+
+    if current > latest_version() {
+        return Err(CommandError::Validation(format!(
+            "Database schema version {} is newer than this app supports ({})",
+            current,
+            latest_version()
+        )));
+    }
+
+    for migration in MIGRATIONS {
+        if migration.version <= current {
+            continue;
+        }
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| CommandError::db("Failed to start transaction", e))?;
+
+        tx.execute(migration.sql)
+            .await
+            .map_err(|e| CommandError::db(&format!("Failed to apply migration {} ({})", migration.version, migration.description), e))?;
+
+        // PRAGMA values can't be bound as parameters
+        let set_version = format!("PRAGMA user_version = {}", migration.version);
+        sqlx::query(&set_version)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| CommandError::db("Failed to update schema version", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| CommandError::db(&format!("Failed to commit migration {}", migration.version), e))?;
+
+        log::info!("Applied migration {}: {}", migration.version, migration.description);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tauri::Manager;
+
+    use crate::commands::boards::duplicate_board;
+    use crate::commands::cards::update_card;
+    use crate::commands::tags::{add_tag_to_card, create_tag, remove_tag_from_card};
+    use crate::db::AppState;
+    use crate::test_support::{seed_card, test_app};
+
+    async fn card_times(state: &AppState, id: &str) -> (i64, i64) {
+        sqlx::query_as("SELECT created_at, updated_at FROM cards WHERE id = ?")
+            .bind(id)
+            .fetch_one(&state.pool())
+            .await
+            .unwrap()
+    }
+
+    /// Wait for the clock to move on, then check `updated_at` did too
+    async fn assert_touched(state: &AppState, before: &mut i64, path: &str) {
+        let (_, updated_at) = card_times(state, "c").await;
+        assert!(updated_at > *before, "{} left updated_at at {}", path, updated_at);
+        *before = updated_at;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    #[tokio::test]
+    async fn every_card_update_path_moves_updated_at_forward() {
+        let (app, _dir) = test_app().await;
+        seed_card(&app, "c").await;
+        create_tag(app.state(), "t".into(), "todo".into(), None, None, None).await.unwrap();
+        let state = app.state::<AppState>();
+        let (_, mut updated_at) = card_times(&state, "c").await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        update_card(app.state(), "c".into(), Some("Renamed".into()), None, None, None, None)
+            .await
+            .unwrap();
+        assert_touched(&state, &mut updated_at, "update_card").await;
+
+        add_tag_to_card(app.state(), "c".into(), "t".into()).await.unwrap();
+        assert_touched(&state, &mut updated_at, "add_tag_to_card").await;
+
+        remove_tag_from_card(app.state(), "c".into(), "t".into()).await.unwrap();
+        assert_touched(&state, &mut updated_at, "remove_tag_from_card").await;
+
+        sqlx::query("UPDATE cards SET color = 'red' WHERE id = 'c'")
+            .execute(&state.writer())
+            .await
+            .unwrap();
+        assert_touched(&state, &mut updated_at, "raw UPDATE").await;
+    }
+
+    #[tokio::test]
+    async fn copied_tags_keep_the_copy_timestamp() {
+        let (app, _dir) = test_app().await;
+        seed_card(&app, "c").await;
+        create_tag(app.state(), "t".into(), "todo".into(), None, None, None).await.unwrap();
+        add_tag_to_card(app.state(), "c".into(), "t".into()).await.unwrap();
+        let state = app.state::<AppState>();
+
+        let copy = duplicate_board(app.state(), "b".into(), None).await.unwrap();
+        let copied_card: String = sqlx::query_scalar("SELECT card_id FROM board_cards WHERE board_id = ?")
+            .bind(&copy.id)
+            .fetch_one(&state.pool())
+            .await
+            .unwrap();
+        let (created_at, updated_at) = card_times(&state, &copied_card).await;
+        assert_eq!(updated_at, created_at);
+    }
+}
@@ -0,0 +1,226 @@
+pub mod migrations;
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
+use tauri::Manager;
+
+use crate::commands::boards::SnapshotBuffer;
+use crate::commands::watch::db_mtime;
+use crate::error::CommandError;
+
+/// File name of the SQLite database inside the app data directory
+pub const DB_FILENAME: &str = "notly.db";
+
+/// Set by a commit hook on the writer connection, cleared once the connection is back
+/// in the pool and the commit's modification time is in `LOCAL_WRITE_MTIME`
+static LOCAL_COMMIT_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Modification time of the database files right after this process's last write.
+/// Lets the file watcher tell our own writes from other processes'
+static LOCAL_WRITE_MTIME: Mutex<Option<SystemTime>> = Mutex::new(None);
+
+/// How long a connection waits for another connection's write lock before failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Connections to one database file
+/// Reads share `pool` and run concurrently. Every write goes through `writer`, a pool
+/// of a single connection, so writes queue up in order instead of racing for SQLite's
+/// write lock (a deferred transaction that loses that race fails with SQLITE_BUSY
+/// without waiting on the busy timeout)
+#[derive(Clone)]
+pub struct Database {
+    pub pool: SqlitePool,
+    pub writer: SqlitePool,
+}
+
+impl Database {
+    pub async fn close(&self) {
+        self.pool.close().await;
+        self.writer.close().await;
+    }
+}
+
+/// Connection pools shared with every command through Tauri managed state
+/// `run()` opens the default database and manages this before any command can run, so
+/// commands never open connections themselves. `init_database` later swaps in pools for
+/// another file; they sit behind a lock for that (and for restoring backups)
+pub struct AppState {
+    inner: RwLock<DbConnection>,
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+    snapshots: SnapshotBuffer,
+}
+
+struct DbConnection {
+    db: Database,
+    path: PathBuf,
+}
+
+impl AppState {
+    pub fn new(db: Database, path: PathBuf) -> Self {
+        Self {
+            inner: RwLock::new(DbConnection { db, path }),
+            watcher: Mutex::new(None),
+            snapshots: SnapshotBuffer::default(),
+        }
+    }
+
+    /// The current pool for reads (a cheap handle clone)
+    pub fn pool(&self) -> SqlitePool {
+        self.read().db.pool.clone()
+    }
+
+    /// The current single-connection pool for writes; anything that inserts, updates
+    /// or deletes uses this rather than `pool`
+    pub fn writer(&self) -> SqlitePool {
+        self.read().db.writer.clone()
+    }
+
+    /// Path of the open database file
+    pub fn path(&self) -> PathBuf {
+        self.read().path.clone()
+    }
+
+    /// Swap in new pools, returning the previous ones so the caller can close them
+    pub fn replace(&self, db: Database, path: PathBuf) -> Database {
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        inner.path = path;
+        std::mem::replace(&mut inner.db, db)
+    }
+
+    /// Keep `watcher` running, stopping the previous one (see `start_db_watch`)
+    pub fn set_watcher(&self, watcher: notify::RecommendedWatcher) {
+        *self.watcher.lock().unwrap_or_else(|e| e.into_inner()) = Some(watcher);
+    }
+
+    /// Canvas snapshots waiting to be written (see `save_canvas_snapshot`)
+    pub fn snapshots(&self) -> &SnapshotBuffer {
+        &self.snapshots
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, DbConnection> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Whether this process committed a write whose modification time isn't recorded yet
+pub fn local_write_pending() -> bool {
+    LOCAL_COMMIT_PENDING.load(Ordering::Acquire)
+}
+
+/// Modification time of the database files right after this process's last write
+pub fn local_write_mtime() -> Option<SystemTime> {
+    *LOCAL_WRITE_MTIME.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Resolve the default database path inside the app data directory
+pub fn default_db_path(app: &tauri::AppHandle) -> Result<PathBuf, CommandError> {
+    let app_data_dir = app.path()
+        .app_data_dir()
+        .map_err(|e| CommandError::internal("Failed to get app data dir", e))?;
+
+    std::fs::create_dir_all(&app_data_dir)
+        .map_err(|e| CommandError::io("Failed to create app data dir", e))?;
+
+    Ok(app_data_dir.join(DB_FILENAME))
+}
+
+/// Open (or create) the database at `db_path` and bring its schema up to date
+/// All connection settings (WAL, foreign keys, busy timeout) live here
+pub async fn connect(db_path: &Path) -> Result<Database, CommandError> {
+    let options = SqliteConnectOptions::new()
+        .filename(db_path)
+        .create_if_missing(true)
+        .foreign_keys(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .busy_timeout(BUSY_TIMEOUT);
+
+    // Opened first so the file and WAL mode exist before the readers connect
+    let writer = SqlitePoolOptions::new()
+        .max_connections(1)
+        .after_connect(|conn, _| {
+            Box::pin(async move {
+                conn.lock_handle().await?.set_commit_hook(|| {
+                    LOCAL_COMMIT_PENDING.store(true, Ordering::Release);
+                    true
+                });
+                Ok(())
+            })
+        })
+        .after_release({
+            let db_path = db_path.to_path_buf();
+            move |_, _| {
+                let db_path = db_path.clone();
+                Box::pin(async move {
+                    // Any transaction is committed by the time the connection comes back
+                    if LOCAL_COMMIT_PENDING.load(Ordering::Acquire) {
+                        *LOCAL_WRITE_MTIME.lock().unwrap_or_else(|e| e.into_inner()) = db_mtime(&db_path);
+                        LOCAL_COMMIT_PENDING.store(false, Ordering::Release);
+                    }
+                    Ok(true)
+                })
+            }
+        })
+        .connect_with(options.clone())
+        .await
+        .map_err(|e| CommandError::db("Failed to open database", e))?;
+
+    migrations::run_migrations(&writer).await?;
+
+    let pool = SqlitePoolOptions::new()
+        .connect_with(options)
+        .await
+        .map_err(|e| CommandError::db("Failed to open database", e))?;
+
+    log::info!("Database ready at: {:?}", db_path);
+    Ok(Database { pool, writer })
+}
+
+#[cfg(test)]
+mod tests {
+    use tauri::Manager;
+
+    use super::AppState;
+    use crate::commands::cards::{get_card, update_card};
+    use crate::test_support::{seed_card, seed_card_on_board, test_app};
+
+    /// Concurrent writes queue on the single writer connection instead of failing
+    /// with SQLITE_BUSY, while reads keep going on the reader pool
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn concurrent_updates_never_fail_busy() {
+        let (app, _dir) = test_app().await;
+        seed_card(&app, "c0").await;
+        for i in 1..10 {
+            seed_card_on_board(&app, &format!("c{}", i)).await;
+        }
+
+        let mut tasks = Vec::new();
+        for i in 0..100 {
+            let handle = app.handle().clone();
+            tasks.push(tokio::spawn(async move {
+                let state = handle.state::<AppState>();
+                let id = format!("c{}", i % 10);
+                let content = format!("{{\"type\":\"doc\",\"n\":{}}}", i);
+                update_card(state, id, Some(format!("Title {}", i)), Some(content), None, None, None)
+                    .await
+                    .map(|_| ())
+            }));
+            let handle = app.handle().clone();
+            tasks.push(tokio::spawn(async move {
+                let id = format!("c{}", i % 10);
+                get_card(handle.state::<AppState>(), id).await.map(|_| ())
+            }));
+        }
+
+        let mut failures = Vec::new();
+        for task in tasks {
+            if let Err(e) = task.await.unwrap() {
+                failures.push(e.to_string());
+            }
+        }
+        assert!(failures.is_empty(), "{} operations failed: {:?}", failures.len(), failures);
+    }
+}
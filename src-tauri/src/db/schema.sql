@@ -1,5 +1,5 @@
 -- Visual Thinking App - SQLite Database Schema
--- Version: 1.0.0
+-- Version: 1.0.0 (migration 1; later changes go in db/migrations.rs)
 
 -- Projects
 CREATE TABLE IF NOT EXISTS projects (
@@ -20,7 +20,8 @@ CREATE TABLE IF NOT EXISTS boards (
     parent_board_id TEXT,
     title TEXT NOT NULL,
     position INTEGER DEFAULT 0,
-    tldraw_snapshot TEXT, -- JSON snapshot of tldraw state
+    tldraw_snapshot BLOB, -- tldraw snapshot: gzip with a 0x01 header byte, or legacy JSON text
+    snapshot_updated_at INTEGER,
     created_at INTEGER NOT NULL,
     updated_at INTEGER NOT NULL,
     FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
@@ -35,11 +36,24 @@ CREATE TABLE IF NOT EXISTS cards (
     color TEXT,
     is_hidden INTEGER DEFAULT 0,
     word_count INTEGER DEFAULT 0,
+    deleted_at INTEGER, -- set while the card is in the trash
     created_at INTEGER NOT NULL,
     updated_at INTEGER NOT NULL,
     metadata TEXT -- JSON
 );
 
+-- Board Cards (which cards live on which board)
+CREATE TABLE IF NOT EXISTS board_cards (
+    board_id TEXT NOT NULL,
+    card_id TEXT NOT NULL,
+    x REAL,
+    y REAL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (board_id, card_id),
+    FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE,
+    FOREIGN KEY (card_id) REFERENCES cards(id) ON DELETE CASCADE
+);
+
 -- Canvas Elements (tldraw shapes + metadata)
 CREATE TABLE IF NOT EXISTS canvas_elements (
     id TEXT PRIMARY KEY,
@@ -68,6 +82,18 @@ CREATE TABLE IF NOT EXISTS files (
     metadata TEXT -- JSON
 );
 
+-- Assets (files stored in the assets folder, deduplicated by content hash)
+CREATE TABLE IF NOT EXISTS assets (
+    id TEXT PRIMARY KEY,
+    hash TEXT UNIQUE, -- SHA-256 of the file contents
+    relative_path TEXT NOT NULL UNIQUE, -- relative to the assets folder, absolute for references
+    file_type TEXT NOT NULL, -- pdf, image, other
+    file_size INTEGER,
+    mime_type TEXT, -- sniffed from the file contents
+    import_mode TEXT NOT NULL DEFAULT 'copy', -- copy, move or reference (file left in place)
+    created_at INTEGER NOT NULL
+);
+
 -- Highlights (PDF and card annotations)
 CREATE TABLE IF NOT EXISTS highlights (
     id TEXT PRIMARY KEY,
@@ -93,6 +119,16 @@ CREATE TABLE IF NOT EXISTS tags (
     created_at INTEGER NOT NULL
 );
 
+-- Card Tags (many-to-many)
+CREATE TABLE IF NOT EXISTS card_tags (
+    card_id TEXT NOT NULL,
+    tag_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (card_id, tag_id),
+    FOREIGN KEY (card_id) REFERENCES cards(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
 -- Tag Relations (many-to-many)
 CREATE TABLE IF NOT EXISTS tag_relations (
     tag_id TEXT NOT NULL,
@@ -145,20 +181,24 @@ CREATE TABLE IF NOT EXISTS journal_entries (
 
 -- Full-Text Search Index (FTS5)
 CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
-    entity_type,
-    entity_id,
+    entity_type UNINDEXED,
+    entity_id UNINDEXED,
     title,
     content,
     tags,
+    indexed_at UNINDEXED, -- ms timestamp, used for date filtering
     tokenize = 'porter unicode61'
 );
 
 -- Performance Indexes
 CREATE INDEX IF NOT EXISTS idx_boards_project ON boards(project_id);
 CREATE INDEX IF NOT EXISTS idx_boards_position ON boards(project_id, position);
+CREATE INDEX IF NOT EXISTS idx_cards_deleted ON cards(deleted_at);
+CREATE INDEX IF NOT EXISTS idx_board_cards_card ON board_cards(card_id);
 CREATE INDEX IF NOT EXISTS idx_canvas_elements_board ON canvas_elements(board_id);
 CREATE INDEX IF NOT EXISTS idx_canvas_elements_type ON canvas_elements(element_type);
 CREATE INDEX IF NOT EXISTS idx_highlights_source ON highlights(source_type, source_id);
+CREATE INDEX IF NOT EXISTS idx_card_tags_tag ON card_tags(tag_id);
 CREATE INDEX IF NOT EXISTS idx_tag_relations_entity ON tag_relations(entity_type, entity_id);
 CREATE INDEX IF NOT EXISTS idx_tag_relations_tag ON tag_relations(tag_id);
 CREATE INDEX IF NOT EXISTS idx_links_source ON links(source_type, source_id);
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+/// Error returned by every command
+/// Serialized as `{ "kind": "notFound", "message": "..." }` so the frontend can
+/// switch on the kind instead of parsing the message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum CommandError {
+    /// The requested entity doesn't exist
+    NotFound(String),
+    /// The input was rejected (bad arguments, unsupported files, ...)
+    Validation(String),
+    /// The entity already exists or would clash with an existing one
+    Conflict(String),
+    /// Filesystem failure
+    Io(String),
+    /// SQLite failure
+    Db(String),
+    /// Anything else (encoding, archives, background tasks, ...)
+    Internal(String),
+}
+
+impl CommandError {
+    pub fn db(context: &str, e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => Self::NotFound(context.to_string()),
+            e => Self::Db(format!("{}: {}", context, e)),
+        }
+    }
+
+    pub fn io(context: &str, e: std::io::Error) -> Self {
+        Self::Io(format!("{}: {}", context, e))
+    }
+
+    pub fn internal(context: &str, e: impl std::fmt::Display) -> Self {
+        Self::Internal(format!("{}: {}", context, e))
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::NotFound(message)
+            | Self::Validation(message)
+            | Self::Conflict(message)
+            | Self::Io(message)
+            | Self::Db(message)
+            | Self::Internal(message) => message,
+        }
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for CommandError {}
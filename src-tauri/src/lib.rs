@@ -1,4 +1,12 @@
 mod commands;
+mod db;
+mod error;
+mod validate;
+
+#[cfg(test)]
+mod test_support;
+
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -8,16 +16,130 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_clipboard_manager::init())
         .invoke_handler(tauri::generate_handler![
+            // Database commands
+            commands::database::init_database,
+            commands::database::optimize_database,
+            commands::database::get_db_diagnostics,
+            commands::database::flush_all,
+            commands::watch::start_db_watch,
             // Asset management commands
+            commands::database::get_app_data_dir,
             commands::database::get_assets_dir,
+            commands::database::get_free_disk_space,
             commands::database::ensure_directory_structure,
             commands::database::copy_file_to_assets,
             commands::database::delete_asset_file,
+            commands::database::get_asset_ref_count,
+            commands::database::get_cards_referencing_asset,
             commands::database::get_asset_path,
+            commands::database::regenerate_thumbnail,
+            commands::database::open_asset_external,
+            commands::database::get_pdf_info,
+            commands::database::rename_asset,
+            commands::database::relink_asset,
+            commands::database::verify_assets,
+            commands::database::get_assets_usage,
+            commands::database::gc_unused_assets,
             commands::database::save_bytes_to_assets,
+            commands::database::paste_image_from_clipboard,
             commands::database::open_assets_folder,
+            // Project commands
+            commands::projects::create_project,
+            commands::projects::get_projects,
+            commands::projects::get_project,
+            commands::projects::update_project,
+            commands::projects::set_project_pinned,
+            commands::projects::delete_project,
+            commands::projects::get_project_stats,
+            commands::projects::set_project_palette,
+            commands::projects::get_project_palette,
+            commands::projects::get_color_usage,
+            commands::projects::get_project_settings,
+            commands::projects::set_project_settings,
+            commands::bundle::export_project,
+            commands::bundle::import_project,
+            // Board commands
+            commands::boards::create_board,
+            commands::boards::update_board,
+            commands::boards::get_boards,
+            commands::boards::get_boards_tree,
+            commands::boards::get_board_ancestors,
+            commands::boards::reorder_boards,
+            commands::boards::duplicate_board,
+            commands::boards::delete_board,
+            commands::boards::save_canvas_snapshot,
+            commands::boards::flush_pending_snapshots,
+            commands::boards::load_canvas_snapshot,
+            // Template commands
+            commands::templates::save_board_as_template,
+            commands::templates::get_board_templates,
+            commands::templates::create_board_from_template,
+            commands::templates::delete_board_template,
+            // Sync commands
+            commands::sync::get_changes_since,
+            commands::sync::get_tombstones_since,
+            commands::sync::purge_tombstones,
+            // App state commands
+            commands::app_state::set_last_opened,
+            commands::app_state::get_last_opened,
+            commands::app_state::get_assets_root,
+            commands::app_state::set_assets_root,
+            // Card commands
+            commands::cards::create_card,
+            commands::cards::create_cards,
+            commands::cards::get_cards,
+            commands::cards::get_card_previews,
+            commands::cards::get_card,
+            commands::cards::get_cards_by_tags,
+            commands::cards::get_untagged_cards,
+            commands::cards::find_duplicate_cards,
+            commands::cards::update_card,
+            commands::cards::move_card,
+            commands::cards::update_card_position,
+            commands::cards::set_card_pinned,
+            commands::cards::get_cards_with_positions,
+            commands::cards::delete_card,
+            commands::cards::bulk_delete_cards,
+            commands::cards::restore_card,
+            commands::cards::purge_deleted_cards,
+            commands::cards::get_card_revisions,
+            commands::cards::restore_card_revision,
+            commands::markdown::export_cards_markdown,
+            commands::markdown::import_markdown,
+            commands::html::export_board_html,
+            // Tag commands
+            commands::tags::create_tag,
+            commands::tags::get_tags,
+            commands::tags::update_tag,
+            commands::tags::delete_tag,
+            commands::tags::merge_tags,
+            commands::tags::create_tag_group,
+            commands::tags::get_tag_groups,
+            commands::tags::update_tag_group,
+            commands::tags::delete_tag_group,
+            commands::tags::add_tag_to_card,
+            commands::tags::remove_tag_from_card,
+            // Search commands
+            commands::search::fts_search,
+            commands::search::search_content,
+            commands::search::fts_index_entity,
+            commands::search::fts_index_batch,
+            commands::search::fts_remove_entity,
+            commands::search::fts_rebuild_index,
+            commands::search::get_search_suggestions,
+            commands::search::clear_search_history,
+            // Dashboard commands
+            commands::dashboard::get_recent_items,
+            // Backup commands
+            commands::backup::create_backup,
+            commands::backup::restore_backup,
+            commands::backup::export_library,
+            commands::backup::import_library,
         ])
         .setup(|app| {
+            let db_path = db::default_db_path(app.handle())?;
+            let db = tauri::async_runtime::block_on(db::connect(&db_path))?;
+            app.manage(db::AppState::new(db, db_path));
 
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -0,0 +1,50 @@
+//! Helpers shared by the unit tests
+
+use tauri::test::MockRuntime;
+use tauri::{App, Manager};
+
+use crate::commands::{boards, cards, projects};
+use crate::db::{self, AppState};
+
+/// A mock app managing an `AppState` on a fresh database file (WAL needs a real file)
+/// The database lives as long as the returned temp dir
+pub(crate) async fn test_app() -> (App<MockRuntime>, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(db::DB_FILENAME);
+    let database = db::connect(&path).await.unwrap();
+
+    let app = tauri::test::mock_app();
+    app.manage(AppState::new(database, path));
+    (app, dir)
+}
+
+/// Create project "p" with board "b" holding card `card_id`
+pub(crate) async fn seed_card(app: &App<MockRuntime>, card_id: &str) {
+    projects::create_project(app.state(), "p".into(), "P".into(), None, None, None, None)
+        .await
+        .unwrap();
+    boards::create_board(app.state(), "b".into(), "p".into(), None, "B".into(), None)
+        .await
+        .unwrap();
+    seed_card_on_board(app, card_id).await;
+}
+
+/// Add card `card_id` to board "b" made by `seed_card`
+pub(crate) async fn seed_card_on_board(app: &App<MockRuntime>, card_id: &str) {
+    cards::create_card(
+        app.state(),
+        "b".into(),
+        card_id.into(),
+        Some("Card".into()),
+        "{}".into(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+}
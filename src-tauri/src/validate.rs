@@ -0,0 +1,131 @@
+use std::sync::OnceLock;
+
+use crate::commands::content::CARD_CONTENT_TYPES;
+use crate::error::CommandError;
+
+// ============================================
+// Input Validation
+// ============================================
+
+/// Longest title accepted for projects, boards and cards
+pub const MAX_TITLE_CHARS: usize = 512;
+
+/// Trim a title and check it fits in `MAX_TITLE_CHARS` with no null bytes
+/// `field` names the argument in the error, e.g. "title"
+pub fn title(field: &str, value: &str) -> Result<String, CommandError> {
+    if value.contains('\0') {
+        return Err(CommandError::Validation(format!("{} must not contain null bytes", field)));
+    }
+
+    let trimmed = value.trim();
+    if trimmed.chars().count() > MAX_TITLE_CHARS {
+        return Err(CommandError::Validation(format!(
+            "{} is too long (at most {} characters)",
+            field, MAX_TITLE_CHARS
+        )));
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Default for `max_content_bytes`: 5 MiB
+pub const DEFAULT_MAX_CONTENT_BYTES: usize = 5 * 1024 * 1024;
+
+/// Largest card content accepted, in bytes
+/// `DEFAULT_MAX_CONTENT_BYTES` unless the `NOTLY_MAX_CONTENT_BYTES` environment
+/// variable holds a positive number; read once per run
+pub fn max_content_bytes() -> usize {
+    static LIMIT: OnceLock<usize> = OnceLock::new();
+    *LIMIT.get_or_init(|| {
+        std::env::var("NOTLY_MAX_CONTENT_BYTES")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .filter(|&limit| limit > 0)
+            .unwrap_or(DEFAULT_MAX_CONTENT_BYTES)
+    })
+}
+
+/// Check card content fits in `max_content_bytes`
+pub fn content(field: &str, value: &str) -> Result<(), CommandError> {
+    let limit = max_content_bytes();
+    if value.len() > limit {
+        return Err(CommandError::Validation(format!(
+            "{} is too large ({} bytes, at most {})",
+            field,
+            value.len(),
+            limit
+        )));
+    }
+    Ok(())
+}
+
+/// `title` for optional fields; None passes through
+pub fn optional_title(field: &str, value: Option<String>) -> Result<Option<String>, CommandError> {
+    value.map(|value| title(field, &value)).transpose()
+}
+
+/// Check a color is a hex string: `#` followed by 3, 4, 6 or 8 hex digits
+pub fn hex_color(field: &str, value: &str) -> Result<String, CommandError> {
+    let value = value.trim();
+    let is_hex = value
+        .strip_prefix('#')
+        .is_some_and(|digits| matches!(digits.len(), 3 | 4 | 6 | 8) && digits.chars().all(|c| c.is_ascii_hexdigit()));
+    if !is_hex {
+        return Err(CommandError::Validation(format!("{} is not a hex color: {}", field, value)));
+    }
+    Ok(value.to_string())
+}
+
+/// Check a card content type is one of `CARD_CONTENT_TYPES`; None means "tiptap"
+pub fn content_type(value: Option<String>) -> Result<String, CommandError> {
+    let value = value.unwrap_or_else(|| "tiptap".to_string());
+    if !CARD_CONTENT_TYPES.contains(&value.as_str()) {
+        return Err(CommandError::Validation(format!(
+            "Unknown content type: {} (expected one of {})",
+            value,
+            CARD_CONTENT_TYPES.join(", ")
+        )));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_accepts_the_maximum_length_in_chars() {
+        // 'é' is two bytes, so this is 1024 bytes but 512 characters
+        let longest = "é".repeat(MAX_TITLE_CHARS);
+        assert_eq!(title("title", &longest).unwrap(), longest);
+    }
+
+    #[test]
+    fn title_rejects_one_char_over_the_maximum() {
+        let too_long = "é".repeat(MAX_TITLE_CHARS + 1);
+        assert!(matches!(title("title", &too_long), Err(CommandError::Validation(_))));
+    }
+
+    #[test]
+    fn title_length_is_counted_after_trimming() {
+        let padded = format!("  {}  ", "a".repeat(MAX_TITLE_CHARS));
+        assert_eq!(title("title", &padded).unwrap(), "a".repeat(MAX_TITLE_CHARS));
+    }
+
+    #[test]
+    fn title_trims_whitespace_only_input_to_empty() {
+        assert_eq!(title("title", " \t\n ").unwrap(), "");
+    }
+
+    #[test]
+    fn title_rejects_null_bytes() {
+        assert!(matches!(title("title", "a\0b"), Err(CommandError::Validation(_))));
+    }
+
+    #[test]
+    fn title_errors_name_the_field() {
+        let too_long = title("name", &"a".repeat(MAX_TITLE_CHARS + 1)).unwrap_err();
+        assert!(too_long.message().starts_with("name "), "{}", too_long);
+        let null = title("description", "\0").unwrap_err();
+        assert!(null.message().starts_with("description "), "{}", null);
+    }
+}